@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::error::CorkError;
+use serde::Deserialize;
+
+/// Widths a value can be viewed and edited in, matching the GUI toggle.
+pub const WIDTHS: [u32; 4] = [8, 16, 32, 64];
+
+/// A BitEdit is one in-place change to the bits of the current value.
+/// Ranges are inclusive on both ends, the lower bit comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum BitEdit {
+    Flip(u32, u32),
+    Set(u32, u32),
+    Clear(u32, u32),
+    Shl(u32),
+    Shr(u32),
+}
+
+pub fn check_width(width: u32) -> Result<u32, CorkError> {
+    if WIDTHS.contains(&width) {
+        Ok(width)
+    } else {
+        Err(CorkError::InvalidWidth(width))
+    }
+}
+
+/// to_unsigned gives the two's complement bits of `value` in `width` bits.
+pub fn to_unsigned(value: i64, width: u32) -> u64 {
+    if width >= 64 {
+        value as u64
+    } else {
+        value as u64 & ((1u64 << width) - 1)
+    }
+}
+
+/// to_signed reads the lower `width` bits of `bits` as a signed number.
+pub fn to_signed(bits: u64, width: u32) -> i64 {
+    if width >= 64 {
+        bits as i64
+    } else {
+        let shift = 64 - width;
+        ((bits << shift) as i64) >> shift
+    }
+}
+
+/// range_mask has the bits `lo` to `hi` (inclusive) set.
+pub fn range_mask(lo: u32, hi: u32) -> u64 {
+    let upper = if hi >= 63 { u64::MAX } else { (1u64 << (hi + 1)) - 1 };
+    upper & (u64::MAX << lo)
+}
+
+/// bit_index checks that `bit` is a bit of a 64 bit value.
+fn bit_index(bit: i64) -> Result<u32, CorkError> {
+    u32::try_from(bit)
+        .ok()
+        .filter(|bit| *bit < 64)
+        .ok_or_else(|| CorkError::Eval(format!("{} is not a bit of a 64 bit value", bit)))
+}
+
+/// genmask has the bits `lo` to `hi` set, like the Linux GENMASK(h, l).
+pub fn genmask(hi: i64, lo: i64) -> Result<u64, CorkError> {
+    let (hi, lo) = (bit_index(hi)?, bit_index(lo)?);
+    if lo > hi {
+        return Err(CorkError::Eval(format!("invalid bit range {}..{}", lo, hi)));
+    }
+    Ok(range_mask(lo, hi))
+}
+
+/// bit_list has the given bits set.
+pub fn bit_list(bits: &[i64]) -> Result<u64, CorkError> {
+    bits.iter().try_fold(0, |mask, bit| Ok(mask | 1 << bit_index(*bit)?))
+}
+
+/// ranges describes the set bits of `bits` as runs, like `bits 8-15, 31`.
+pub fn ranges(bits: u64) -> String {
+    let mut runs = Vec::new();
+    let mut rest = bits;
+    while rest != 0 {
+        let lo = rest.trailing_zeros();
+        let len = (rest >> lo).trailing_ones();
+        let hi = lo + len - 1;
+        runs.push(if lo == hi { lo.to_string() } else { format!("{}-{}", lo, hi) });
+        rest &= !range_mask(lo, hi);
+    }
+    match bits.count_ones() {
+        0 => "no bits".to_string(),
+        1 => format!("bit {}", runs[0]),
+        _ => format!("bits {}", runs.join(", ")),
+    }
+}
+
+impl BitEdit {
+    /// apply performs the edit on `value` seen as a `width`-bit number, and
+    /// returns the result read back as a signed `width`-bit number.
+    pub fn apply(&self, value: i64, width: u32) -> Result<i64, CorkError> {
+        let width = check_width(width)?;
+        let bits = to_unsigned(value, width);
+        let bits = match *self {
+            BitEdit::Flip(lo, hi) => bits ^ self.mask(lo, hi, width)?,
+            BitEdit::Set(lo, hi) => bits | self.mask(lo, hi, width)?,
+            BitEdit::Clear(lo, hi) => bits & !self.mask(lo, hi, width)?,
+            BitEdit::Shl(n) => bits.checked_shl(n).unwrap_or(0),
+            BitEdit::Shr(n) => bits.checked_shr(n).unwrap_or(0),
+        };
+        Ok(to_signed(to_unsigned(bits as i64, width), width))
+    }
+
+    fn mask(&self, lo: u32, hi: u32, width: u32) -> Result<u64, CorkError> {
+        if lo > hi {
+            return Err(CorkError::Eval(format!("invalid bit range {}..{}", lo, hi)));
+        }
+        if hi >= width {
+            return Err(CorkError::BitOutOfRange { bit: hi, width });
+        }
+        Ok(range_mask(lo, hi))
+    }
+}
+
+/// BitEdit is shown as the directive that makes it, for the history.
+impl fmt::Display for BitEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (op, lo, hi) = match *self {
+            BitEdit::Flip(lo, hi) => ("flip", lo, hi),
+            BitEdit::Set(lo, hi) => ("set-bits", lo, hi),
+            BitEdit::Clear(lo, hi) => ("clear-bits", lo, hi),
+            BitEdit::Shl(n) => return write!(f, "shl {}", n),
+            BitEdit::Shr(n) => return write!(f, "shr {}", n),
+        };
+        if lo == hi {
+            write!(f, "{} {}", op, lo)
+        } else {
+            write!(f, "{} {}..{}", op, lo, hi)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bit_edit_apply() {
+        let cases = [
+            (BitEdit::Flip(5, 5), 0, 64, 0x20),
+            (BitEdit::Flip(7, 7), 0, 8, -128),
+            (BitEdit::Set(3, 3), 0x1, 16, 0x9),
+            (BitEdit::Clear(12, 15), 0xf0ff, 32, 0x00ff),
+            (BitEdit::Clear(12, 15), -1, 16, 0x0fff),
+            (BitEdit::Set(0, 63), 0, 64, -1),
+            (BitEdit::Shl(4), 0x1f, 8, -16),
+            (BitEdit::Shr(4), -1, 16, 0x0fff),
+            (BitEdit::Shr(64), -1, 64, 0),
+        ];
+        for (edit, value, width, expected) in cases {
+            assert_eq!(edit.apply(value, width).unwrap(), expected, "{:?}", edit);
+        }
+    }
+
+    #[test]
+    fn test_bit_edit_errors() {
+        assert_eq!(
+            BitEdit::Flip(8, 8).apply(0, 8),
+            Err(CorkError::BitOutOfRange { bit: 8, width: 8 })
+        );
+        assert_eq!(BitEdit::Flip(0, 0).apply(0, 12), Err(CorkError::InvalidWidth(12)));
+        assert!(BitEdit::Set(4, 2).apply(0, 8).is_err());
+    }
+
+    #[test]
+    fn test_masks_and_ranges() {
+        assert_eq!(genmask(15, 8).unwrap(), 0xff00);
+        assert_eq!(genmask(63, 0).unwrap(), u64::MAX);
+        assert!(genmask(8, 15).is_err());
+        assert!(genmask(64, 0).is_err());
+        assert_eq!(bit_list(&[0, 3, 7]).unwrap(), 0x89);
+        assert!(bit_list(&[-1]).is_err());
+        assert_eq!(ranges(0x8000_ff00), "bits 8-15, 31");
+        assert_eq!(ranges(0x89), "bits 0, 3, 7");
+        assert_eq!(ranges(u64::MAX), "bits 0-63");
+        assert_eq!(ranges(0x10), "bit 4");
+        assert_eq!(ranges(0), "no bits");
+    }
+}
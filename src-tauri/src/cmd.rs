@@ -1,35 +1,90 @@
 use clap::crate_version;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
+use std::env;
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, IsTerminal};
 use std::path::PathBuf;
 use std::process::exit;
-use strum::IntoEnumIterator;
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 
 use crate::{
-    format::{FormatRadix, OutputFormat},
+    bits,
+    encoding,
+    format::{align_columns, fmt_bytes, Cyclic, FormatRadix, OutputFormat, Style, View, Views},
+    hashes::HashAlgo,
     options::Options,
     error,
-    expression,
+    expression::{self, eval::Value},
+    filter,
     config::Config,
+    layout::Layout,
+    modules::Modules,
+    paging::VaParts,
+    report::{self, Report, ReportFormat},
+    session::Session,
+    status,
 };
 
 pub fn cmd_main(mut config: Config, options: Options) {
+    // text is the output for people, the other formats are for scripts
+    let report = options.format.parse::<ReportFormat>().ok();
+    // colored only looks at the environment, not at where the output goes,
+    // so colors are left out off a terminal unless CLICOLOR_FORCE asks for them
+    let forced = env::var_os("CLICOLOR_FORCE").is_some_and(|force| force != "0");
+    if report.is_some() || !(io::stdout().is_terminal() || forced) {
+        colored::control::set_override(false);
+    }
     config.override_from_options(&options);
+    let mut session = match Session::load(options.config.as_ref()) {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("Failed to load the session: {}", err);
+            exit(1);
+        }
+    };
+    if let Err(err) = session.configure(&config) {
+        eprintln!("Failed to parse config: {}", err);
+        exit(1);
+    }
 
-    if let Some(expr_vec) = &options.expr {
+    let failed = if let Some(expr_vec) = &options.expr {
         let expr_str = expr_vec.join(" ");
-        inline_evaluate(&expr_str, &config, &options);
+        match report {
+            _ if options.filter => filter_evaluate([expr_str], &config, &mut session),
+            Some(format) => report_evaluate([expr_str], format, &mut config, &mut session),
+            None => {
+                inline_evaluate(&expr_str, &config, &options, &mut session);
+                false
+            }
+        }
     } else if let Some(file_path) = &options.file {
-        script_evaluate(file_path, &mut config);
+        script_evaluate(file_path, &mut config, &mut session, report, options.filter)
+    } else if options.stdin.is_some() {
+        let lines = read_lines(io::stdin().lock());
+        lines_evaluate(lines, &mut config, &mut session, report, options.filter)
     } else if options.interactive {
-        interactive(&mut config);
+        interactive(&mut config, &mut session);
+        false
+    } else {
+        false
+    };
+
+    if let Err(err) = session.save() {
+        eprintln!("Failed to save evaluation history: {}", err);
+    }
+    if failed {
+        exit(1);
     }
 }
 
-fn script_evaluate(file_path: &str, config: &mut Config) {
+fn script_evaluate(
+    file_path: &str,
+    config: &mut Config,
+    session: &mut Session,
+    report: Option<ReportFormat>,
+    filter: bool,
+) -> bool {
     let file = File::open(file_path);
 
     let file = match file {
@@ -40,57 +95,205 @@ fn script_evaluate(file_path: &str, config: &mut Config) {
         }
     };
 
-    let lines = io::BufReader::new(file).lines();
+    lines_evaluate(read_lines(io::BufReader::new(file)), config, session, report, filter)
+}
+
+/// read_lines gives the lines of `reader`, and exits on the first one that
+/// can't be read.
+fn read_lines(reader: impl BufRead) -> impl Iterator<Item = String> {
+    reader.lines().map(|line| match line {
+        Ok(line) => line,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    })
+}
+
+/// lines_evaluate runs `lines` like a script, as a report or through the
+/// filter when asked to, and tells whether a line failed. A script stops
+/// at the first line that fails.
+fn lines_evaluate(
+    lines: impl Iterator<Item = String>,
+    config: &mut Config,
+    session: &mut Session,
+    report: Option<ReportFormat>,
+    filter: bool,
+) -> bool {
+    if filter {
+        return filter_evaluate(lines, config, session);
+    }
+    if let Some(format) = report {
+        return report_evaluate(lines, format, config, session);
+    }
+
+    let mut of = OutputFormat::default()
+        .with_format_radix(*config.output_radix())
+        .with_punctuate_number(*config.punctuate_output())
+        .with_style(*config.style())
+        .with_bytes(session.width, session.endian);
+
+    for line in lines {
+        if let Err(e) = proccess_command(line, session, &mut of, config) {
+            eprintln!("{}", e);
+            return true;
+        }
+    }
+    false
+}
 
-    let mut ans = 0;
+/// report_evaluate prints a report for every line of `lines` that has a
+/// result, for `--format json|tsv`. Unlike the text output it goes on after
+/// a failed line, and tells at the end whether one failed.
+fn report_evaluate(
+    lines: impl IntoIterator<Item = String>,
+    format: ReportFormat,
+    config: &mut Config,
+    session: &mut Session,
+) -> bool {
     let mut of = OutputFormat::default()
         .with_format_radix(*config.output_radix())
-        .with_punctuate_number(*config.punctuate_output());
+        .with_punctuate_number(*config.punctuate_output())
+        .with_style(*config.style())
+        .with_bytes(session.width, session.endian);
 
+    if format == ReportFormat::Tsv {
+        println!("{}", report::tsv_header());
+    }
+    let mut failed = false;
     for line in lines {
-        let line = match line {
-            Ok(line) => line,
-            Err(err) => {
-                eprintln!("{}", err);
-                exit(1);
-            }
-        };
-        match proccess_command(line, &mut ans, &mut of, config) {
-            Ok(_) => continue,
-            Err(e) => {
-                eprintln!("{}", e);
-                exit(1);
-            }
-        };
+        if let Some(report) = report_line(&line, session, &mut of, config) {
+            failed |= report.failed();
+            println!("{}", report.write(format));
+        }
+    }
+    failed
+}
+
+/// report_line evaluates `line` like proccess_command does, but gives a
+/// Report of the result instead of printing it. Empty lines and set
+/// directives have no result, and give none.
+fn report_line(line: &str, session: &mut Session, of: &mut OutputFormat, config: &mut Config) -> Option<Report> {
+    if line.trim().is_empty() {
+        return None;
     }
+    let report = Report::new(line, session.width);
+    let command = match expression::parse_line(line, config) {
+        Ok(command) => command,
+        Err(err) => return Some(report.with_error(&err)),
+    };
+    let result = match command {
+        expression::Command::Empty => return None,
+        expression::Command::Set(_) => {
+            return proccess_command(line.to_string(), session, of, config)
+                .err()
+                .map(|err| report.with_error(&err));
+        }
+        expression::Command::Expr(expr) => match expression::eval::eval_value(&expr, &session.context()) {
+            Ok(Value::Int(val)) => Ok((val, of.format_radix())),
+            Ok(value) => return Some(report.with_text(value.to_string())),
+            Err(err) => Err(err.into()),
+        },
+        expression::Command::Convert(conversion) => match conversion.value_in(&session.context()) {
+            Ok(Value::Int(val)) => Ok((val, conversion.radix())),
+            Ok(value) => return Some(report.with_text(value.to_string())),
+            Err(err) => Err(err.into()),
+        },
+        expression::Command::Bits(edit) => session
+            .edit_bits(edit)
+            .map(|val| (val, of.format_radix()))
+            .map_err(Into::into),
+        _ => Err(anyhow!("only expressions, conversions and bit directives have a report")),
+    };
+    Some(match result {
+        Ok((val, radix)) => {
+            session.ans = val;
+            session.history.record(line.trim(), val, radix, config.mode());
+            report.with_value(val, of)
+        }
+        Err(err) => report.with_error(&err),
+    })
 }
 
-fn inline_evaluate(expr_str: &str, config: &Config, options: &Options) {
+/// filter_evaluate prints `lines` with their prefixed numbers and `$(...)`
+/// expressions rewritten. What fails to evaluate is printed as it was, the
+/// error goes to stderr, and whether anything failed is told at the end.
+fn filter_evaluate(lines: impl IntoIterator<Item = String>, config: &Config, session: &mut Session) -> bool {
+    let of = OutputFormat::default()
+        .with_format_radix(*config.output_radix())
+        .with_punctuate_number(*config.punctuate_output())
+        .with_style(*config.style())
+        .with_bytes(session.width, session.endian);
+
+    let mut failed = false;
+    for line in lines {
+        let (rewritten, errors) = filter::rewrite(&line, |expr| {
+            filter_expr(expr, session, &of, config).with_context(|| format!("Failed to evaluate \"{}\"", expr))
+        });
+        for err in &errors {
+            eprintln!("{:#}", err);
+        }
+        failed |= !errors.is_empty();
+        println!("{}", rewritten);
+    }
+    failed
+}
+
+/// filter_expr gives the value of `expr` in the output format, or in the
+/// one it converts to.
+fn filter_expr(expr: &str, session: &mut Session, of: &OutputFormat, config: &Config) -> Result<String> {
+    let (val, text) = match expression::parse_line(expr, config)? {
+        expression::Command::Expr(expr) => match expression::eval::eval_value(&expr, &session.context())? {
+            Value::Int(val) => (val, of.fmt(val)),
+            value => return Ok(value.to_string()),
+        },
+        // a lone number is also a conversion, to the other base, but here it
+        // takes the output format like the numbers in the text do
+        expression::Command::Convert(conversion) => {
+            let val = match conversion.value_in(&session.context())? {
+                Value::Int(val) => val,
+                value => return Ok(value.to_string()),
+            };
+            let text = if conversion.explicit() {
+                conversion.fmt(val, of)
+            } else if conversion.unsigned() {
+                of.fmt_unsigned(val as u64)
+            } else {
+                of.fmt(val)
+            };
+            (val, text)
+        }
+        _ => return Err(anyhow!("only expressions and conversions can be filtered")),
+    };
+    session.ans = val;
+    Ok(text)
+}
+
+fn inline_evaluate(expr_str: &str, config: &Config, options: &Options, session: &mut Session) {
     match expression::parse_line(expr_str, config) {
         Ok(command) => match command {
-            expression::Command::Expr(expr) => match expression::eval::eval_expr(&expr, 0) {
-                Ok(ans) => {
+            expression::Command::Expr(expr) => match expression::eval::eval_value(&expr, &session.context()) {
+                Ok(Value::Int(ans)) => {
                     if options.all {
-                        for radix in FormatRadix::iter() {
-                            println!(
-                                "{:>21}: {}",
-                                radix.to_string(),
-                                OutputFormat::default()
-                                    .with_format_radix(radix)
-                                    .with_punctuate_number(*config.punctuate_output())
-                                    .fmt(ans),
-                            );
-                        }
+                        let of = OutputFormat::default()
+                            .with_punctuate_number(*config.punctuate_output())
+                            .with_style(*config.style())
+                            .with_bytes(session.width, session.endian);
+                        let radices: Views = FormatRadix::radices().map(View::Radix).collect();
+                        println!("{}", radices.table(ans, &of));
                     } else {
                         println!(
                             "{}",
                             OutputFormat::default()
                                 .with_format_radix(*config.output_radix())
                                 .with_punctuate_number(*config.punctuate_output())
+                                .with_style(*config.style())
+                                .with_bytes(session.width, session.endian)
                                 .fmt(ans),
                         );
                     }
                 }
+                Ok(value) => println!("{}", value),
                 Err(err) => {
                     eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
                     exit(1);
@@ -100,29 +303,145 @@ fn inline_evaluate(expr_str: &str, config: &Config, options: &Options) {
                 eprintln!("Set directive not allowed in inline-expression");
                 exit(1);
             }
-            expression::Command::Convert(conversion) => match conversion.value(0) {
-                Ok(ans) => {
+            expression::Command::Bits(_) => {
+                eprintln!("Bit directive not allowed in inline-expression");
+                exit(1);
+            }
+            expression::Command::Decode(decode) => {
+                let decoded = decode
+                    .value_with(&session.context())
+                    .and_then(|val| Ok((val, session.layouts.get(decode.layout())?)));
+                match decoded {
+                    Ok((val, layout)) => print_decode(decode.layout(), layout, val, config),
+                    Err(err) => {
+                        eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                        exit(1);
+                    }
+                }
+            }
+            expression::Command::Flags(flags) => match flags.value_with(&session.context()) {
+                Ok(val) => println!("{}", flags.table().render(bits::to_unsigned(val, session.width))),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::Encode(encode) => match encode_value(&encode, session) {
+                Ok(ans) => println!(
+                    "{}",
+                    OutputFormat::default()
+                        .with_format_radix(*config.output_radix())
+                        .with_punctuate_number(*config.punctuate_output())
+                        .with_style(*config.style())
+                        .with_bytes(session.width, session.endian)
+                        .fmt(ans),
+                ),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::Va(va) => match va.value_with(&session.context()) {
+                Ok(val) => print_va(
+                    &va.scheme().unwrap_or(session.paging).split(val),
+                    &OutputFormat::default()
+                        .with_format_radix(*config.output_radix())
+                        .with_punctuate_number(*config.punctuate_output())
+                        .with_style(*config.style()),
+                ),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::MkVa(mkva) => match mkva_value(&mkva, session) {
+                Ok(ans) => println!(
+                    "{}",
+                    OutputFormat::default()
+                        .with_format_radix(*config.output_radix())
+                        .with_punctuate_number(*config.punctuate_output())
+                        .with_style(*config.style())
+                        .with_bytes(session.width, session.endian)
+                        .fmt_unsigned(ans as u64),
+                ),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::Module(_) => {
+                eprintln!("Module directive not allowed in inline-expression");
+                exit(1);
+            }
+            expression::Command::WhereIs(expr) => match expression::eval::eval_expr_with(&expr, &session.context()) {
+                Ok(val) => print_whereis(&session.modules, val, &OutputFormat::default()
+                    .with_format_radix(*config.output_radix())
+                    .with_punctuate_number(*config.punctuate_output())
+                    .with_style(*config.style())),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::WhatIs(expr) => match expression::eval::eval_expr_with(&expr, &session.context()) {
+                Ok(val) => print_whatis(val, &OutputFormat::default()
+                    .with_format_radix(*config.output_radix())
+                    .with_punctuate_number(*config.punctuate_output())
+                    .with_style(*config.style())),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::Cyclic(expr) => match cyclic_pattern(&expr, session) {
+                Ok(pattern) => println!("{}", pattern),
+                Err(err) => {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            },
+            expression::Command::HashLookup(lookup) => {
+                let printed = lookup
+                    .value_with(&session.context())
+                    .map_err(anyhow::Error::from)
+                    .and_then(|val| {
+                        let of = OutputFormat::default()
+                            .with_format_radix(*config.output_radix())
+                            .with_punctuate_number(*config.punctuate_output())
+                            .with_style(*config.style());
+                        print_hash_lookup(lookup.algo(), val, session, &of)
+                    });
+                if let Err(err) = printed {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            }
+            expression::Command::XorBrute(expr) => {
+                if let Err(err) = print_xorbrute(&expr, session) {
+                    eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
+                    exit(1);
+                }
+            }
+            expression::Command::Modules => print_modules(&session.modules, config),
+            expression::Command::History => print_history(session, config),
+            expression::Command::Convert(conversion) => match conversion.value_in(&session.context()) {
+                Ok(Value::Int(ans)) => {
                     if options.all {
-                        for radix in FormatRadix::iter() {
-                            println!(
-                                "{:>21}: {}",
-                                radix.to_string(),
-                                OutputFormat::default()
-                                    .with_format_radix(radix)
-                                    .with_punctuate_number(*config.punctuate_output())
-                                    .fmt(ans),
-                            );
-                        }
+                        let of = OutputFormat::default()
+                            .with_punctuate_number(*config.punctuate_output())
+                            .with_style(*config.style())
+                            .with_bytes(session.width, session.endian);
+                        let radices: Views = FormatRadix::radices().map(View::Radix).collect();
+                        println!("{}", radices.table(ans, &of));
                     } else {
-                        println!(
-                            "{}",
-                            OutputFormat::default()
-                                .with_format_radix(conversion.radix())
-                                .with_punctuate_number(*config.punctuate_output())
-                                .fmt(ans),
-                        );
+                        let of = OutputFormat::default()
+                            .with_punctuate_number(*config.punctuate_output())
+                            .with_style(*config.style())
+                            .with_bytes(session.width, session.endian);
+                        println!("{}", conversion.fmt_in(ans, &of, config.view()));
                     }
                 }
+                Ok(value) => println!("{}", value),
                 Err(err) => {
                     eprintln!("Failed to evaluate \"{}\": {}", expr_str, err);
                     exit(1);
@@ -139,7 +458,7 @@ fn inline_evaluate(expr_str: &str, config: &Config, options: &Options) {
     }
 }
 
-fn interactive(config: &mut Config) {
+fn interactive(config: &mut Config, session: &mut Session) {
     if *config.header() {
         welcome(config);
     }
@@ -161,13 +480,14 @@ fn interactive(config: &mut Config) {
 
     let mut of = OutputFormat::default()
         .with_format_radix(*config.output_radix())
-        .with_punctuate_number(*config.punctuate_output());
-    let mut ans = 0;
+        .with_punctuate_number(*config.punctuate_output())
+        .with_style(*config.style())
+        .with_bytes(session.width, session.endian);
     loop {
         match rl.readline(config.prompt()) {
             Ok(line) => {
                 let _ = rl.add_history_entry(&line);
-                match proccess_command(line, &mut ans, &mut of, config) {
+                match proccess_command(line, session, &mut of, config) {
                     Ok(_) => continue,
                     Err(e) => {
                         eprintln!("{}", e);
@@ -193,28 +513,64 @@ fn interactive(config: &mut Config) {
     }
 }
 
-fn proccess_command(line: String, ans: &mut i64, of: &mut OutputFormat, config: &mut Config) -> Result<()> {
+/// fmt_result shows a result in the views of `config`, or in the output
+/// format when there are none.
+fn fmt_result(val: i64, of: &OutputFormat, config: &Config) -> String {
+    if config.view().is_empty() {
+        of.fmt(val)
+    } else {
+        config.view().table(val, of)
+    }
+}
+
+/// set_style gives `style` with the output style key `key` set to `value`,
+/// or None when `key` is not a style key.
+fn set_style(mut style: Style, key: &str, value: &str) -> Result<Option<Style>> {
+    let invalid = || error::CorkError::InvalidValueForKey {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let on_off = || match value {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(invalid()),
+    };
+    match key {
+        "uppercase" => style.uppercase = on_off()?,
+        "zero_pad" => style.zero_pad = on_off()?,
+        "prefix" => style.prefix = value.parse()?,
+        "separator" => style.separator = Some(value.parse().map_err(|_| invalid())?),
+        "group_size" => style.group_size = Some(value.parse().map_err(|_| invalid())?),
+        _ => return Ok(None),
+    }
+    Ok(Some(style))
+}
+
+fn proccess_command(line: String, session: &mut Session, of: &mut OutputFormat, config: &mut Config) -> Result<()> {
     let command = expression::parse_line(&line, config)?;
     match command {
-        expression::Command::Expr(expr) => {
-            let val = expression::eval::eval_expr(&expr, *ans)?;
-            *ans = val;
-            println!("{}", of.fmt(val));
-        }
+        expression::Command::Expr(expr) => match expression::eval::eval_value(&expr, &session.context())? {
+            Value::Int(val) => {
+                session.ans = val;
+                session.history.record(line.trim(), val, of.format_radix(), config.mode());
+                println!("{}", fmt_result(val, of, config));
+            }
+            // encoded bytes and text are shown as they are, ans keeps the last number
+            value => println!("{}", value),
+        },
         expression::Command::Set(set) => {
             if set[0] == "of" {
-                match set[1].as_str() {
-                    "hex" => of.set_format_radix(FormatRadix::Hex),
-                    "dec" => of.set_format_radix(FormatRadix::Decimal),
-                    "oct" => of.set_format_radix(FormatRadix::Octal),
-                    "bin" => of.set_format_radix(FormatRadix::Binary),
-                    _ => {
-                        return Err(error::CorkError::InvalidValueForKey {
-                            key: set[0].clone(),
-                            value: set[1].clone(),
-                        }.into());
-                    }
-                }
+                let radix = set[1].parse().map_err(|_| error::CorkError::InvalidValueForKey {
+                    key: set[0].clone(),
+                    value: set[1].clone(),
+                })?;
+                of.set_format_radix(radix);
+            } else if set[0] == "view" {
+                let views = set[1].parse().map_err(|_| error::CorkError::InvalidValueForKey {
+                    key: set[0].clone(),
+                    value: set[1].clone(),
+                })?;
+                config.set_view(views);
             } else if set[0] == "mode" {
                 match set[1].as_str() {
                     "hex" => {config.set_mode("hex".to_string());},
@@ -226,26 +582,251 @@ fn proccess_command(line: String, ans: &mut i64, of: &mut OutputFormat, config:
                         }.into());
                     }
                 }
+            } else if let Some(style) = set_style(*config.style(), &set[0], &set[1])? {
+                config.set_style(style);
+                of.set_style(style);
+            } else if set[0] == "width" {
+                let width = set[1].parse().map_err(|_| error::CorkError::InvalidValueForKey {
+                    key: set[0].clone(),
+                    value: set[1].clone(),
+                })?;
+                session.width = bits::check_width(width)?;
+                of.set_bytes(session.width, session.endian);
+            } else if set[0] == "paging" {
+                session.paging = set[1].parse()?;
+            } else if set[0] == "endian" {
+                session.endian = set[1].parse()?;
+                of.set_bytes(session.width, session.endian);
+            } else if set[0] == "cyclic_alphabet" {
+                session.cyclic = Cyclic::new(&set[1], session.cyclic.n())?;
+            } else if set[0] == "cyclic_n" {
+                let n = set[1].parse().map_err(|_| error::CorkError::InvalidValueForKey {
+                    key: set[0].clone(),
+                    value: set[1].clone(),
+                })?;
+                session.cyclic = Cyclic::new(&session.cyclic.alphabet(), n)?;
             } else {
                 return Err(error::CorkError::InvalidKey(set[0].clone()).into());
             }
         }
-        expression::Command::Convert(conversion) => {
-            let val = conversion.value(*ans)?;
-            *ans = val;
-            println!(
-                "{}",
-                OutputFormat::default()
-                    .with_format_radix(conversion.radix())
-                    .with_punctuate_number(of.punctuate_number())
-                    .fmt(val)
-            );
+        expression::Command::Convert(conversion) => match conversion.value_in(&session.context())? {
+            Value::Int(val) => {
+                session.ans = val;
+                session.history.record(line.trim(), val, conversion.radix(), config.mode());
+                println!("{}", conversion.fmt_in(val, &of.with_bytes(session.width, session.endian), config.view()));
+            }
+            value => println!("{}", value),
+        },
+        expression::Command::Bits(edit) => {
+            let val = session.edit_bits(edit)?;
+            session.history.record(line.trim(), val, of.format_radix(), config.mode());
+            // the bits are shown as they are at the width, without a sign
+            let of = of.with_bytes(session.width, session.endian);
+            println!("{}", if config.view().is_empty() {
+                of.fmt_unsigned(bits::to_unsigned(val, session.width))
+            } else {
+                config.view().table(val, &of)
+            });
         }
+        expression::Command::Decode(decode) => {
+            let val = decode.value_with(&session.context())?;
+            let layout = session.layouts.get(decode.layout())?;
+            print_decode(decode.layout(), layout, val, config);
+            session.ans = val;
+        }
+        expression::Command::Flags(flags) => {
+            let val = flags.value_with(&session.context())?;
+            println!("{}", flags.table().render(bits::to_unsigned(val, session.width)));
+            session.ans = val;
+        }
+        expression::Command::Encode(encode) => {
+            let val = encode_value(&encode, session)?;
+            session.ans = val;
+            session.history.record(line.trim(), val, of.format_radix(), config.mode());
+            println!("{}", of.fmt(val));
+        }
+        expression::Command::Va(va) => {
+            let val = va.value_with(&session.context())?;
+            print_va(&va.scheme().unwrap_or(session.paging).split(val), of);
+            session.ans = val;
+        }
+        expression::Command::MkVa(mkva) => {
+            let val = mkva_value(&mkva, session)?;
+            session.ans = val;
+            session.history.record(line.trim(), val, of.format_radix(), config.mode());
+            println!("{}", of.fmt_unsigned(val as u64));
+        }
+        expression::Command::Module(module) => {
+            let (base, image_base, size) = module.values(&session.context())?;
+            session.modules.define(module.name(), base, image_base, size);
+            session.ans = base;
+            session.history.record(line.trim(), base, of.format_radix(), config.mode());
+            println!("{}", of.fmt(base));
+        }
+        expression::Command::WhereIs(expr) => {
+            let val = expression::eval::eval_expr_with(&expr, &session.context())?;
+            print_whereis(&session.modules, val, of);
+            session.ans = val;
+        }
+        expression::Command::WhatIs(expr) => {
+            let val = expression::eval::eval_expr_with(&expr, &session.context())?;
+            print_whatis(val, of);
+            session.ans = val;
+        }
+        expression::Command::Cyclic(expr) => {
+            println!("{}", cyclic_pattern(&expr, session)?);
+        }
+        expression::Command::HashLookup(lookup) => {
+            let val = lookup.value_with(&session.context())?;
+            print_hash_lookup(lookup.algo(), val, session, of)?;
+            session.ans = val;
+        }
+        expression::Command::XorBrute(expr) => print_xorbrute(&expr, session)?,
+        expression::Command::Modules => print_modules(&session.modules, config),
+        expression::Command::History => print_history(session, config),
         expression::Command::Empty => println!(),
     };
     Ok(())
 }
 
+fn encode_value(encode: &expression::EncodeDirective, session: &Session) -> Result<i64, error::CorkError> {
+    let values = encode.field_values(&session.context())?;
+    session.layouts.get(encode.layout())?.encode(encode.layout(), &values)
+}
+
+fn print_decode(name: &str, layout: &Layout, val: i64, config: &Config) {
+    println!(
+        "{} = {}",
+        name,
+        OutputFormat::default()
+            .with_format_radix(FormatRadix::Hex)
+            .with_punctuate_number(*config.punctuate_output())
+            .with_style(*config.style())
+            .fmt_unsigned(bits::to_unsigned(val, layout.width())),
+    );
+    for line in layout.decode(val).lines() {
+        println!("  {}", line);
+    }
+}
+
+fn mkva_value(mkva: &expression::MkVaDirective, session: &Session) -> Result<i64, error::CorkError> {
+    let values = mkva.field_values(&session.context())?;
+    mkva.scheme().unwrap_or(session.paging).build(&values)
+}
+
+fn print_va(parts: &VaParts, of: &OutputFormat) {
+    println!("{} ({})", of.fmt_unsigned(parts.addr as u64), parts.scheme);
+    for line in parts.render(of).lines() {
+        println!("  {}", line);
+    }
+}
+
+fn cyclic_pattern(len: &expression::Expr, session: &Session) -> Result<String, error::CorkError> {
+    let len = expression::eval::eval_expr_with(len, &session.context())?;
+    let len = usize::try_from(len).map_err(|_| error::CorkError::Eval(format!("invalid pattern length {}", len)))?;
+    Ok(fmt_bytes(&session.cyclic.pattern(len)?))
+}
+
+fn print_hash_lookup(algo: HashAlgo, val: i64, session: &mut Session, of: &OutputFormat) -> Result<()> {
+    let (found, searched) = session.hash_lookup(algo, val)?;
+    if found.is_empty() {
+        println!("no {} match for {} in {} names", algo, of.fmt(val), searched);
+    }
+    for name in found {
+        println!("{}", name);
+    }
+    Ok(())
+}
+
+/// print_xorbrute shows the single-byte keys that give the most printable
+/// results, with the start of each result.
+fn print_xorbrute(expr: &expression::Expr, session: &Session) -> Result<(), error::CorkError> {
+    const SHOWN_KEYS: usize = 10;
+    const SHOWN_BYTES: usize = 48;
+    let ctx = session.context();
+    let data = expression::eval::eval_value(expr, &ctx)?.into_bytes(&ctx);
+    let rows: Vec<_> = encoding::xor_brute(&data)
+        .into_iter()
+        .take(SHOWN_KEYS)
+        .map(|guess| {
+            let shown = &guess.plain[..guess.plain.len().min(SHOWN_BYTES)];
+            let more = if guess.plain.len() > SHOWN_BYTES { "..." } else { "" };
+            vec![
+                format!("0x{:02x}", guess.key),
+                format!("{:.0}%", guess.printable * 100.0),
+                format!("\"{}\"{}", fmt_bytes(shown), more),
+            ]
+        })
+        .collect();
+    println!("{}", align_columns(&rows));
+    Ok(())
+}
+
+fn print_whatis(val: i64, of: &OutputFormat) {
+    let meanings = status::whatis(val);
+    if meanings.is_empty() {
+        println!("{} is not a known status code", of.fmt_unsigned(val as u64));
+        return;
+    }
+    let mut rows = Vec::new();
+    for meaning in meanings {
+        rows.push(vec![meaning.kind.to_string(), meaning.name, meaning.description]);
+        if let Some(detail) = meaning.detail {
+            rows.push(vec![String::new(), String::new(), detail]);
+        }
+    }
+    println!("{}", align_columns(&rows));
+}
+
+fn print_whereis(modules: &Modules, addr: i64, of: &OutputFormat) {
+    let addr_str = of.fmt_unsigned(addr as u64);
+    match modules.locate(addr) {
+        Some(location) => {
+            let module = modules.get(&location.module).expect("located modules exist");
+            let other = if location.image {
+                format!("live {}", of.fmt_unsigned(module.rebase(addr) as u64))
+            } else {
+                format!("image {}", of.fmt_unsigned(module.image_base().wrapping_add(location.offset) as u64))
+            };
+            println!("{} = {}+{} ({})", addr_str, location.module, of.fmt(location.offset), other);
+        }
+        None => println!("{} is not in any module", addr_str),
+    }
+}
+
+fn print_modules(modules: &Modules, config: &Config) {
+    let of = OutputFormat::default()
+        .with_format_radix(FormatRadix::Hex)
+        .with_punctuate_number(*config.punctuate_output())
+        .with_style(*config.style());
+    for module in modules.iter() {
+        println!(
+            "{} = {}, {}, {}",
+            module.name(),
+            of.fmt_unsigned(module.base() as u64),
+            of.fmt_unsigned(module.image_base() as u64),
+            of.fmt_unsigned(module.size() as u64),
+        );
+    }
+}
+
+fn print_history(session: &Session, config: &Config) {
+    for entry in session.history.entries() {
+        println!(
+            "{:>5}{} {} = {}",
+            format!("${}", entry.id()),
+            if entry.pinned() { "*" } else { " " },
+            entry.input(),
+            OutputFormat::default()
+                .with_format_radix(entry.radix())
+                .with_punctuate_number(*config.punctuate_output())
+                .with_style(*config.style())
+                .with_bytes(session.width, session.endian)
+                .fmt(entry.result()),
+        );
+    }
+}
+
 fn welcome(config: &Config) {
     println!("Cork, version {}", crate_version!());
     // println!("Welcome to cork - a calculator for hex-lovers!");
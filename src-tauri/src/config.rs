@@ -4,7 +4,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{format::FormatRadix, options::Options};
+use crate::{
+    format::{Endian, FormatRadix, Style, Views},
+    options::Options,
+};
 use anyhow::Result as AResult;
 use colored::Colorize;
 use getset::{Getters, Setters};
@@ -28,6 +31,29 @@ pub struct Config {
     #[serde(default)]
     punctuate_output: bool,
 
+    #[serde(flatten)]
+    #[getset(set = "pub")]
+    style: Style,
+
+    #[serde(default)]
+    #[getset(set = "pub")]
+    view: Views,
+
+    #[serde(default = "default_width")]
+    width: u32,
+
+    #[serde(default)]
+    endian: Endian,
+
+    #[serde(default = "default_cyclic_alphabet")]
+    cyclic_alphabet: String,
+
+    #[serde(default = "default_cyclic_n")]
+    cyclic_n: usize,
+
+    #[serde(default)]
+    wordlist: Option<PathBuf>,
+
     #[serde(default = "default_mode")]
     #[getset(set = "pub")]
     mode: String,
@@ -49,6 +75,22 @@ impl Config {
             self.output_radix = FormatRadix::Binary;
         }
 
+        if options.uppercase {
+            self.style.uppercase = true;
+        }
+        if options.zero_pad {
+            self.style.zero_pad = true;
+        }
+        if let Some(prefix) = &options.prefix {
+            self.style.prefix = prefix.parse().expect("clap only allows known prefixes");
+        }
+        if options.separator.is_some() {
+            self.style.separator = options.separator;
+        }
+        if options.group_size.is_some() {
+            self.style.group_size = options.group_size;
+        }
+
         if options.history {
             self.history = true;
         }
@@ -64,6 +106,13 @@ impl Config {
             history: false,
             output_radix: FormatRadix::Hex,
             punctuate_output: false,
+            style: Style::default(),
+            view: Views::default(),
+            width: default_width(),
+            endian: Endian::Little,
+            cyclic_alphabet: default_cyclic_alphabet(),
+            cyclic_n: default_cyclic_n(),
+            wordlist: None,
             mode: "hex".to_string()
         }
     }
@@ -81,6 +130,18 @@ fn default_history() -> bool {
     false
 }
 
+fn default_width() -> u32 {
+    64
+}
+
+fn default_cyclic_alphabet() -> String {
+    "abcdefghijklmnopqrstuvwxyz".to_string()
+}
+
+fn default_cyclic_n() -> usize {
+    4
+}
+
 fn default_mode() -> String {
     "hex".to_string()
 }
@@ -103,6 +164,39 @@ fn config_locations() -> Vec<PathBuf> {
     }
 }
 
+/// config_dir is the directory of the config file in use, falling back to
+/// `$HOME/.config/cork` when there is none. Files that belong to the config
+/// (history, layouts, the hash lookup wordlist) are kept there.
+fn config_dir<T: AsRef<Path>>(user_path: Option<T>) -> Option<PathBuf> {
+    let config_path = match user_path {
+        Some(user_path) => Some(user_path.as_ref().to_path_buf()),
+        None => config_locations()
+            .into_iter()
+            .find(|loc| loc.exists() && loc.is_file()),
+    };
+    match config_path {
+        Some(path) => Some(path.parent().map(Path::to_path_buf).unwrap_or_default()),
+        None => {
+            let mut dir = home::home_dir()?;
+            dir.push(".config");
+            dir.push("cork");
+            Some(dir)
+        }
+    }
+}
+
+pub fn history_location<T: AsRef<Path>>(user_path: Option<T>) -> Option<PathBuf> {
+    config_dir(user_path).map(|dir| dir.join("cork_history.json"))
+}
+
+pub fn layouts_location<T: AsRef<Path>>(user_path: Option<T>) -> Option<PathBuf> {
+    config_dir(user_path).map(|dir| dir.join("layouts.yml"))
+}
+
+pub fn wordlist_location<T: AsRef<Path>>(user_path: Option<T>) -> Option<PathBuf> {
+    config_dir(user_path).map(|dir| dir.join("exports.txt"))
+}
+
 pub fn read_config<T: AsRef<Path>>(user_path: Option<T>) -> AResult<Config> {
     let mut content = String::new();
     if let Some(user_path) = user_path {
@@ -127,6 +221,7 @@ pub fn read_config<T: AsRef<Path>>(user_path: Option<T>) -> AResult<Config> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::format::Prefix;
 
     #[test]
     fn test_config_deserialize() {
@@ -135,7 +230,18 @@ header: false
 history: true
 output_radix: Octal
 mode: dec
-punctuate_output: true";
+width: 32
+endian: big
+cyclic_alphabet: ABCD
+cyclic_n: 3
+wordlist: /tmp/exports.txt
+punctuate_output: true
+uppercase: true
+zero_pad: true
+prefix: h
+separator: ','
+group_size: 2
+view: hex,signed";
         let config: Config = serde_yaml::from_str(config_str).unwrap();
         let expected_config = Config {
             prompt: String::from("$"),
@@ -143,6 +249,19 @@ punctuate_output: true";
             history: true,
             output_radix: FormatRadix::Octal,
             punctuate_output: true,
+            style: Style {
+                uppercase: true,
+                zero_pad: true,
+                prefix: Prefix::Suffix,
+                separator: Some(','),
+                group_size: Some(2),
+            },
+            view: "hex,signed".parse().unwrap(),
+            width: 32,
+            endian: Endian::Big,
+            cyclic_alphabet: String::from("ABCD"),
+            cyclic_n: 3,
+            wordlist: Some(PathBuf::from("/tmp/exports.txt")),
             mode: String::from("dec"),
         };
         assert_eq!(config, expected_config);
@@ -159,6 +278,13 @@ output_radix: Octal";
             history: default_history(),
             output_radix: FormatRadix::Octal,
             punctuate_output: false,
+            style: Style::default(),
+            view: Views::default(),
+            width: default_width(),
+            endian: Endian::Little,
+            cyclic_alphabet: default_cyclic_alphabet(),
+            cyclic_n: default_cyclic_n(),
+            wordlist: None,
             mode: String::from("hex"),
         };
         assert_eq!(config, expected_config);
@@ -174,6 +300,13 @@ output_radix: Octal";
             history: default_history(),
             output_radix: FormatRadix::default(),
             punctuate_output: false,
+            style: Style::default(),
+            view: Views::default(),
+            width: default_width(),
+            endian: Endian::Little,
+            cyclic_alphabet: default_cyclic_alphabet(),
+            cyclic_n: default_cyclic_n(),
+            wordlist: None,
             mode: String::from("hex"),
         };
         assert_eq!(config, expected_config);
@@ -0,0 +1,549 @@
+use std::{fmt, str::FromStr};
+
+use crate::{bits, error::CorkError};
+use serde::{Deserialize, Serialize};
+
+const B64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const CROCKFORD32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// b64_encode gives the standard, padded base64 form of `bytes`.
+pub fn b64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (idx, &b)| acc | (b as u32) << (16 - 8 * idx));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                out.push(B64_ALPHABET[(group >> (18 - 6 * idx) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// b64_decode reads standard or URL-safe base64. Whitespace is skipped and
+/// the padding may be left out.
+pub fn b64_decode(text: &[u8]) -> Result<Vec<u8>, CorkError> {
+    let invalid = |reason: &str| CorkError::Eval(format!("invalid base64: {}", reason));
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut group, mut bits) = (0u32, 0u32);
+    let mut padding = false;
+    for &c in text.iter().filter(|c| !c.is_ascii_whitespace()) {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => {
+                padding = true;
+                continue;
+            }
+            _ => return Err(invalid(&format!("unexpected character {:?}", c as char))),
+        };
+        if padding {
+            return Err(invalid("data after padding"));
+        }
+        group = (group << 6) | sextet as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((group >> bits) as u8);
+        }
+    }
+    if bits >= 6 {
+        return Err(invalid("truncated input"));
+    }
+    Ok(out)
+}
+
+/// hex_encode gives two lowercase hex digits per byte.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// hex_decode reads pairs of hex digits. Whitespace between bytes is skipped.
+pub fn hex_decode(text: &[u8]) -> Result<Vec<u8>, CorkError> {
+    let digits: Vec<u8> = text.iter().copied().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(CorkError::Eval("invalid hex string: odd number of digits".to_string()));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| CorkError::Eval(format!("invalid hex string: {}", String::from_utf8_lossy(pair))))
+        })
+        .collect()
+}
+
+/// cstr_escape writes every byte as a `\xNN` escape, ready to paste into C
+/// or Python source.
+pub fn cstr_escape(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("\\x{:02x}", b)).collect()
+}
+
+/// url_encode percent-encodes every byte but the unreserved characters of
+/// RFC 3986.
+pub fn url_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// url_decode reads `%XX` escapes and `+` as a space.
+pub fn url_decode(text: &[u8]) -> Result<Vec<u8>, CorkError> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut iter = text.iter();
+    while let Some(&c) = iter.next() {
+        match c {
+            b'%' => {
+                let pair: Vec<u8> = iter.by_ref().take(2).copied().collect();
+                out.extend(hex_decode(&pair).ok().filter(|b| b.len() == 1).ok_or_else(|| {
+                    CorkError::Eval(format!("invalid URL escape %{}", String::from_utf8_lossy(&pair)))
+                })?);
+            }
+            b'+' => out.push(b' '),
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// uleb128_encode writes `value` seven bits at a time, low bits first,
+/// setting the top bit of every byte but the last. Protobuf varints are the
+/// same encoding.
+pub fn uleb128_encode(mut value: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// sleb128_encode is uleb128_encode for signed values: it stops once the
+/// remaining bits are all copies of the sign bit of the last byte.
+pub fn sleb128_encode(mut value: i64) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// leb128_groups collects the 7-bit groups of the first LEB128 value in
+/// `bytes`, along with the number of bits read. Bytes after the value are
+/// ignored. The tenth group of a signed value may carry sign bits past the
+/// 64th.
+fn leb128_groups(bytes: &[u8], encoding: &str, signed: bool) -> Result<(u64, u32), CorkError> {
+    let (mut value, mut shift) = (0u64, 0u32);
+    for &byte in bytes {
+        let group = (byte & 0x7f) as u64;
+        if shift >= 64 || (shift == 63 && group > 1 && !(signed && group == 0x7f)) {
+            return Err(CorkError::Eval(format!("{} value does not fit in 64 bits", encoding)));
+        }
+        value |= group << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok((value, shift));
+        }
+    }
+    Err(CorkError::Truncated(encoding.to_string()))
+}
+
+/// uleb128_decode reads the first ULEB128 (or varint) value in `bytes`.
+pub fn uleb128_decode(bytes: &[u8]) -> Result<u64, CorkError> {
+    leb128_groups(bytes, "uleb128", false).map(|(value, _)| value)
+}
+
+/// sleb128_decode reads the first SLEB128 value in `bytes`, extending the
+/// sign bit of its last group.
+pub fn sleb128_decode(bytes: &[u8]) -> Result<i64, CorkError> {
+    let (value, bits) = leb128_groups(bytes, "sleb128", true)?;
+    if bits < 64 && value >> (bits - 1) & 1 == 1 {
+        return Ok((value | u64::MAX << bits) as i64);
+    }
+    Ok(value as i64)
+}
+
+/// zigzag maps signed values to unsigned ones so that small magnitudes stay
+/// small: 0, -1, 1, -2 become 0, 1, 2, 3, as protobuf sint fields do.
+pub fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// gray gives the reflected binary Gray code of `value`, in which
+/// consecutive values differ in a single bit.
+pub fn gray(value: u64) -> u64 {
+    value ^ (value >> 1)
+}
+
+pub fn ungray(mut code: u64) -> u64 {
+    let mut value = code;
+    while code != 0 {
+        code >>= 1;
+        value ^= code;
+    }
+    value
+}
+
+/// bcd_kind names the BCD layout, a decimal digit per nibble when packed or
+/// per byte when unpacked.
+fn bcd_kind(packed: bool) -> (u32, &'static str) {
+    if packed {
+        (4, "packed")
+    } else {
+        (8, "unpacked")
+    }
+}
+
+/// bcd_encode gives the BCD form of `value`, lowest digit in the lowest bits.
+pub fn bcd_encode(value: i64, packed: bool) -> Result<u64, CorkError> {
+    let (digit_bits, kind) = bcd_kind(packed);
+    if value < 0 {
+        return Err(CorkError::Eval(format!("{} has no {} BCD form", value, kind)));
+    }
+    let (mut rest, mut code, mut shift) = (value as u64, 0, 0);
+    loop {
+        if shift >= 64 {
+            return Err(CorkError::Eval(format!("{} has too many digits for {} BCD", value, kind)));
+        }
+        code |= (rest % 10) << shift;
+        rest /= 10;
+        shift += digit_bits;
+        if rest == 0 {
+            return Ok(code);
+        }
+    }
+}
+
+/// bcd_decode reads `code` as BCD. Every digit has to be 0 to 9, and the
+/// upper nibble of unpacked digits has to be clear.
+pub fn bcd_decode(code: u64, packed: bool) -> Result<u64, CorkError> {
+    let (digit_bits, kind) = bcd_kind(packed);
+    let mut value = 0;
+    for shift in (0..64).step_by(digit_bits as usize).rev() {
+        let digit = code >> shift & ((1 << digit_bits) - 1);
+        if digit > 9 {
+            return Err(CorkError::Eval(format!("{:#x} is not {} BCD", code, kind)));
+        }
+        value = value * 10 + digit;
+    }
+    Ok(value)
+}
+
+/// excess_encode gives the excess-`k` form of `value` in `width` bits: the
+/// value plus k, so that -k is stored as all zeros.
+pub fn excess_encode(value: i64, k: u64, width: u32) -> Result<u64, CorkError> {
+    let code = value as i128 + k as i128;
+    if code < 0 || code > bits::range_mask(0, width - 1) as i128 {
+        return Err(CorkError::Eval(format!("{} does not fit in {} bit excess-{}", value, width, k)));
+    }
+    Ok(code as u64)
+}
+
+pub fn excess_decode(code: u64, k: u64) -> Result<i64, CorkError> {
+    i64::try_from(code as i128 - k as i128)
+        .map_err(|_| CorkError::Eval(format!("{:#x} in excess-{} does not fit in 64 bits", code, k)))
+}
+
+/// ones_encode gives the ones' complement form of `value` in `width` bits,
+/// where a negative value is its magnitude with every bit flipped.
+pub fn ones_encode(value: i64, width: u32) -> Result<u64, CorkError> {
+    let limit = (1i128 << (width - 1)) - 1;
+    if (value as i128).abs() > limit {
+        return Err(CorkError::Eval(format!("{} does not fit in {} bit ones' complement", value, width)));
+    }
+    let value = if value < 0 { value - 1 } else { value };
+    Ok(bits::to_unsigned(value, width))
+}
+
+/// ones_decode reads the lower `width` bits of `code` as ones' complement.
+/// Negative zero reads as 0.
+pub fn ones_decode(code: u64, width: u32) -> i64 {
+    let value = bits::to_signed(code, width);
+    if value < 0 {
+        value + 1
+    } else {
+        value
+    }
+}
+
+/// check_radix checks that numbers can be written in `radix` with the
+/// digits 0-9 and a-z.
+pub fn check_radix(radix: u32) -> Result<u32, CorkError> {
+    match radix {
+        2..=36 => Ok(radix),
+        _ => Err(CorkError::Eval(format!("{} is not a valid radix, expected 2 to 36", radix))),
+    }
+}
+
+/// An Alphabet is a set of digits numbers are written with when 0-9a-z in
+/// order is not what IDs use.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Crockford's base32, which leaves out I, L, O and U.
+    #[default]
+    Crockford32,
+    /// Bitcoin's base58, which leaves out 0, O, I and l.
+    Base58,
+}
+
+impl Alphabet {
+    pub fn base(&self) -> u32 {
+        self.digits().len() as u32
+    }
+
+    fn digits(&self) -> &'static [u8] {
+        match self {
+            Alphabet::Crockford32 => CROCKFORD32_ALPHABET,
+            Alphabet::Base58 => BASE58_ALPHABET,
+        }
+    }
+
+    /// digit gives the value of `c`. Crockford's base32 is read without
+    /// regard to case, with I and L read as 1 and O as 0.
+    fn digit(&self, c: char) -> Option<u64> {
+        let c = match self {
+            Alphabet::Crockford32 => match c.to_ascii_uppercase() {
+                'I' | 'L' => '1',
+                'O' => '0',
+                c => c,
+            },
+            Alphabet::Base58 => c,
+        };
+        self.digits().iter().position(|&d| d as char == c).map(|d| d as u64)
+    }
+
+    pub fn encode(&self, mut value: u64) -> String {
+        let digits = self.digits();
+        let base = digits.len() as u64;
+        let mut out = Vec::new();
+        loop {
+            out.push(digits[(value % base) as usize]);
+            value /= base;
+            if value == 0 {
+                break;
+            }
+        }
+        out.iter().rev().map(|&d| d as char).collect()
+    }
+
+    /// decode reads `text` as a number. Hyphens, which Crockford's base32
+    /// allows for readability, and underscores are skipped.
+    pub fn decode(&self, text: &str) -> Result<u64, CorkError> {
+        let base = self.digits().len() as u64;
+        text.chars().filter(|&c| c != '-' && c != '_').try_fold(0u64, |value, c| {
+            let digit = self
+                .digit(c)
+                .ok_or_else(|| CorkError::Eval(format!("{:?} is not a {} digit", c, self)))?;
+            value
+                .checked_mul(base)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or_else(|| CorkError::Eval(format!("{} does not fit in 64 bits", text)))
+        })
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alphabet::Crockford32 => write!(f, "crockford32"),
+            Alphabet::Base58 => write!(f, "base58"),
+        }
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "crockford32" => Ok(Alphabet::Crockford32),
+            "base58" => Ok(Alphabet::Base58),
+            _ => Err(CorkError::Eval(format!("{} is not a known alphabet", s))),
+        }
+    }
+}
+
+/// xor_bytes xors `data` with `key`, repeating the key as needed.
+pub fn xor_bytes(data: &[u8], key: &[u8]) -> Result<Vec<u8>, CorkError> {
+    if key.is_empty() {
+        return Err(CorkError::Eval("the xor key is empty".to_string()));
+    }
+    Ok(data.iter().zip(key.iter().cycle()).map(|(d, k)| d ^ k).collect())
+}
+
+/// An XorGuess is the result of xoring data with a single-byte key, with
+/// the share of its bytes that are printable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XorGuess {
+    pub key: u8,
+    pub printable: f64,
+    pub plain: Vec<u8>,
+}
+
+/// xor_brute tries every single-byte key on `data`. The guesses come best
+/// first: by printable ratio, then by how many bytes are letters or spaces.
+pub fn xor_brute(data: &[u8]) -> Vec<XorGuess> {
+    let textlike = |plain: &[u8]| plain.iter().filter(|b| b.is_ascii_alphabetic() || **b == b' ').count();
+    let mut guesses: Vec<_> = (0..=u8::MAX)
+        .map(|key| {
+            let plain: Vec<u8> = data.iter().map(|b| b ^ key).collect();
+            XorGuess { key, printable: printable_ratio(&plain), plain }
+        })
+        .collect();
+    guesses.sort_by(|a, b| {
+        b.printable
+            .total_cmp(&a.printable)
+            .then_with(|| textlike(&b.plain).cmp(&textlike(&a.plain)))
+    });
+    guesses
+}
+
+/// printable_ratio is the share of printable ASCII and common whitespace in
+/// `bytes`.
+fn printable_ratio(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+    let printable = bytes
+        .iter()
+        .filter(|b| matches!(b, 0x20..=0x7e | b'\t' | b'\n' | b'\r'))
+        .count();
+    printable as f64 / bytes.len() as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_b64_round_trip() {
+        let cases: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"\xff\xfe\x00\x01", "//4AAQ=="),
+        ];
+        for (bytes, text) in cases {
+            assert_eq!(b64_encode(bytes), text);
+            assert_eq!(b64_decode(text.as_bytes()).unwrap(), bytes);
+        }
+        assert_eq!(b64_decode(b"Zm9v YmE").unwrap(), b"fooba");
+        assert_eq!(b64_decode(b"__4AAQ").unwrap(), b"\xff\xfe\x00\x01");
+        assert!(b64_decode(b"Zm9vY").is_err());
+        assert!(b64_decode(b"Zg==Zg").is_err());
+    }
+
+    #[test]
+    fn test_hex_and_escapes() {
+        assert_eq!(hex_encode(b"AB\x00"), "414200");
+        assert_eq!(hex_decode(b"41 42 00").unwrap(), b"AB\x00");
+        assert!(hex_decode(b"414").is_err());
+        assert!(hex_decode(b"4g").is_err());
+        assert_eq!(cstr_escape(b"AB"), "\\x41\\x42");
+        assert_eq!(url_encode(b"a b/c~"), "a%20b%2Fc~");
+        assert_eq!(url_decode(b"a%20b+c").unwrap(), b"a b c");
+        assert!(url_decode(b"%4").is_err());
+    }
+
+    #[test]
+    fn test_xor() {
+        assert_eq!(xor_bytes(b"abc", b"\x01\x02").unwrap(), b"``b");
+        assert!(xor_bytes(b"abc", b"").is_err());
+
+        let secret = xor_bytes(b"Hello there, general", b"\x5a").unwrap();
+        let guesses = xor_brute(&secret);
+        assert_eq!(guesses.len(), 256);
+        assert_eq!(guesses[0].key, 0x5a);
+        assert_eq!(guesses[0].plain, b"Hello there, general");
+        assert_eq!(guesses[0].printable, 1.0);
+    }
+
+    #[test]
+    fn test_leb128() {
+        assert_eq!(uleb128_encode(624485), [0xe5, 0x8e, 0x26]);
+        assert_eq!(uleb128_encode(0), [0]);
+        assert_eq!(uleb128_encode(u64::MAX).len(), 10);
+        assert_eq!(sleb128_encode(-123456), [0xc0, 0xbb, 0x78]);
+        assert_eq!(sleb128_encode(63), [0x3f]);
+        assert_eq!(sleb128_encode(64), [0xc0, 0x00]);
+        assert_eq!(sleb128_encode(-64), [0x40]);
+        for value in [0, 1, -1, 63, 64, -64, -65, i64::MAX, i64::MIN] {
+            assert_eq!(sleb128_decode(&sleb128_encode(value)), Ok(value));
+            assert_eq!(uleb128_decode(&uleb128_encode(value as u64)), Ok(value as u64));
+        }
+        assert_eq!(uleb128_decode(&[0xe5, 0x8e, 0x26, 0xff]), Ok(624485));
+        assert_eq!(uleb128_decode(&[0xe5, 0x8e]), Err(CorkError::Truncated("uleb128".to_string())));
+        assert!(uleb128_decode(&[0xff; 10]).is_err());
+
+        assert_eq!([0, -1, 1, -2, 2].map(zigzag), [0, 1, 2, 3, 4]);
+        assert_eq!(zigzag(i64::MIN), u64::MAX);
+        assert_eq!(unzigzag(u64::MAX), i64::MIN);
+        assert_eq!(unzigzag(3), -2);
+    }
+
+    #[test]
+    fn test_integer_codes() {
+        for value in [0, 1, 2, 7, 0x1234, u64::MAX] {
+            assert_eq!(ungray(gray(value)), value);
+        }
+        assert_eq!(gray(2), 3);
+        assert_eq!(bcd_encode(1234, true).unwrap(), 0x1234);
+        assert_eq!(bcd_encode(1234, false).unwrap(), 0x01020304);
+        assert_eq!(bcd_decode(0x1234, true).unwrap(), 1234);
+        assert_eq!(bcd_decode(0x0901, false).unwrap(), 91);
+        assert!(bcd_decode(0x1a, true).is_err());
+        assert!(bcd_decode(0x11, false).is_err());
+        assert!(bcd_encode(-1, true).is_err());
+        assert!(bcd_encode(123456789, false).is_err());
+        assert_eq!(excess_encode(-3, 127, 8).unwrap(), 124);
+        assert_eq!(excess_decode(124, 127).unwrap(), -3);
+        assert!(excess_encode(200, 127, 8).is_err());
+        assert_eq!(excess_decode(0, 1 << 63).unwrap(), i64::MIN);
+        assert_eq!(ones_encode(-1, 8).unwrap(), 0xfe);
+        assert_eq!(ones_decode(0xfe, 8), -1);
+        assert_eq!(ones_decode(0xff, 8), 0);
+        assert!(ones_encode(-128, 8).is_err());
+        assert!(ones_encode(i64::MIN, 64).is_err());
+    }
+
+    #[test]
+    fn test_alphabets() {
+        assert_eq!(Alphabet::Crockford32.encode(0), "0");
+        assert_eq!(Alphabet::Crockford32.encode(1234), "16J");
+        assert_eq!(Alphabet::Crockford32.decode("16j").unwrap(), 1234);
+        assert_eq!(Alphabet::Crockford32.decode("1O-L").unwrap(), 0x401);
+        assert!(Alphabet::Crockford32.decode("U").is_err());
+        assert_eq!(Alphabet::Base58.encode(57), "z");
+        assert_eq!(Alphabet::Base58.encode(58), "21");
+        assert_eq!(Alphabet::Base58.decode(&Alphabet::Base58.encode(u64::MAX)).unwrap(), u64::MAX);
+        assert!(Alphabet::Base58.decode("0").is_err());
+        assert!(Alphabet::Base58.decode("zzzzzzzzzzzz").is_err());
+        assert_eq!("base58".parse::<Alphabet>().unwrap(), Alphabet::Base58);
+    }
+}
@@ -9,6 +9,22 @@ pub enum CorkError {
     InvalidValueForKey { value: String, key: String },
     #[error("{0} is not a valid key")]
     InvalidKey(String),
+    #[error("history entry {0} does not exist")]
+    NoHistoryEntry(u64),
+    #[error("{0} is not a valid width, expected 8, 16, 32 or 64")]
+    InvalidWidth(u32),
+    #[error("bit {bit} is out of range for width {width}")]
+    BitOutOfRange { bit: u32, width: u32 },
+    #[error("{0} is not a known layout")]
+    UnknownLayout(String),
+    #[error("layout {layout} has no field {field}")]
+    UnknownField { layout: String, field: String },
+    #[error("{value} does not fit in the {bits} bit(s) of field {field}")]
+    FieldOverflow { field: String, value: i64, bits: u32 },
+    #[error("{0} is not a known module")]
+    UnknownModule(String),
+    #[error("{0} input ends in the middle of a value")]
+    Truncated(String),
     #[error("parsing error:\n{0}")]
     Parse(#[from] Box<PestRuleError>),
 }
\ No newline at end of file
@@ -1,11 +1,22 @@
+use crate::bits::{self, BitEdit};
+use crate::encoding::{self, Alphabet};
 use crate::error::CorkError;
-use crate::format::FormatRadix;
+use crate::flags::{self, FlagTable};
+use crate::format::{self, Cyclic, Endian, FormatRadix, OutputFormat, Views};
+use crate::hashes::HashAlgo;
+use crate::history::History;
+use crate::insn;
+use crate::modules::Modules;
+use crate::net;
+use crate::paging::PagingScheme;
+use crate::timestamp::{self, Epoch};
 use once_cell::sync::Lazy;
 use pest::iterators::{Pair, Pairs};
 use pest::pratt_parser::PrattParser;
 use pest::Parser;
 use pest_derive::Parser;
 use std::fmt;
+use std::iter::Peekable;
 use std::num::ParseIntError;
 use std::ops::Index;
 use std::str::FromStr;
@@ -22,12 +33,20 @@ struct CommandParser;
 use pest::error::Error as PestError;
 pub(crate) type PestRuleError = PestError<Rule>;
 
-/// An Expr is either a node (which corresponds to a binary operation) or a leaf (which corresponds
-/// to a number).
+/// An Expr is either a node (which corresponds to a binary operation or a function call) or a leaf
+/// (which corresponds to a number, an IP address, a flag name like `PROT_READ`, `ans`, a history
+/// reference like `$3`, or a module name or string literal given as a function argument).
 #[derive(Debug, PartialEq, Eq)]
 pub enum Expr {
     BinOp(BinOpExpr),
+    Call(String, Vec<Expr>),
     Num(i64, Radix),
+    Hist(u64),
+    Name(String),
+    Str(Vec<u8>),
+    Ipv4(u32),
+    Ipv6(u128),
+    Flag(String),
     Ans,
 }
 
@@ -110,6 +129,8 @@ impl Index<usize> for SetDirective {
 pub struct ConvDirective {
     expr: Expr,
     radix: FormatRadix,
+    explicit: bool,
+    unsigned: bool,
 }
 
 impl ConvDirective {
@@ -117,9 +138,55 @@ impl ConvDirective {
         eval::eval_expr(&self.expr, ans)
     }
 
+    pub fn value_with(&self, ctx: &eval::EvalContext) -> Result<i64, CorkError> {
+        eval::eval_expr_with(&self.expr, ctx)
+    }
+
+    /// value_in is value_with for the results that aren't a number: an
+    /// IPv6 address converted to `ipv6` keeps all of its 128 bits.
+    pub fn value_in(&self, ctx: &eval::EvalContext) -> Result<eval::Value, CorkError> {
+        match eval::eval_value(&self.expr, ctx)? {
+            addr @ eval::Value::Ipv6(_) if self.radix == FormatRadix::Ipv6 => Ok(addr),
+            value => value.int(ctx).map(eval::Value::Int),
+        }
+    }
+
     pub fn radix(&self) -> FormatRadix {
         self.radix
     }
+
+    /// explicit tells whether the radix was given with `to`, rather than
+    /// being the other base of a lone number.
+    pub fn explicit(&self) -> bool {
+        self.explicit
+    }
+
+    pub fn unsigned(&self) -> bool {
+        self.unsigned
+    }
+
+    /// fmt shows `val` in the radix of the conversion, in the style of `of`.
+    /// A lone number written without a sign is shown as the bits it was
+    /// written with, so `ffff_ffff_ffff_ffff` converts to
+    /// 18446744073709551615 rather than -1.
+    pub fn fmt(&self, val: i64, of: &OutputFormat) -> String {
+        let of = of.with_format_radix(self.radix);
+        if self.unsigned {
+            of.fmt_unsigned(val as u64)
+        } else {
+            of.fmt(val)
+        }
+    }
+
+    /// fmt_in shows `val` in `views` when there are any, as any other result.
+    /// A conversion written with `to` keeps to the radix it names.
+    pub fn fmt_in(&self, val: i64, of: &OutputFormat, views: &Views) -> String {
+        if self.explicit || views.is_empty() {
+            self.fmt(val, of)
+        } else {
+            views.table(val, of)
+        }
+    }
 }
 
 impl fmt::Display for ConvDirective {
@@ -128,6 +195,143 @@ impl fmt::Display for ConvDirective {
     }
 }
 
+/// A DecodeDirective is a command of the form "decode <layout> [expr]".
+/// Without an expression, `ans` is decoded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DecodeDirective {
+    layout: String,
+    expr: Expr,
+}
+
+impl DecodeDirective {
+    pub fn layout(&self) -> &str {
+        &self.layout
+    }
+
+    pub fn value_with(&self, ctx: &eval::EvalContext) -> Result<i64, CorkError> {
+        eval::eval_expr_with(&self.expr, ctx)
+    }
+}
+
+/// A FlagsDirective is a command of the form "flags <table> [expr]".
+/// Without an expression, `ans` is shown.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FlagsDirective {
+    table: FlagTable,
+    expr: Expr,
+}
+
+impl FlagsDirective {
+    pub fn table(&self) -> FlagTable {
+        self.table
+    }
+
+    pub fn value_with(&self, ctx: &eval::EvalContext) -> Result<i64, CorkError> {
+        eval::eval_expr_with(&self.expr, ctx)
+    }
+}
+
+/// An EncodeDirective is a command of the form "encode <layout> [field=expr]*".
+#[derive(Debug, PartialEq, Eq)]
+pub struct EncodeDirective {
+    layout: String,
+    fields: Vec<(String, Expr)>,
+}
+
+impl EncodeDirective {
+    pub fn layout(&self) -> &str {
+        &self.layout
+    }
+
+    pub fn field_values(&self, ctx: &eval::EvalContext) -> Result<Vec<(String, i64)>, CorkError> {
+        eval_fields(&self.fields, ctx)
+    }
+}
+
+/// A VaDirective is a command of the form "va [scheme] [expr]". Without a
+/// scheme the session default is used, without an expression `ans` is split.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VaDirective {
+    scheme: Option<PagingScheme>,
+    expr: Expr,
+}
+
+impl VaDirective {
+    pub fn scheme(&self) -> Option<PagingScheme> {
+        self.scheme
+    }
+
+    pub fn value_with(&self, ctx: &eval::EvalContext) -> Result<i64, CorkError> {
+        eval::eval_expr_with(&self.expr, ctx)
+    }
+}
+
+/// A MkVaDirective is a command of the form "mkva [scheme] [level=expr]*".
+#[derive(Debug, PartialEq, Eq)]
+pub struct MkVaDirective {
+    scheme: Option<PagingScheme>,
+    fields: Vec<(String, Expr)>,
+}
+
+impl MkVaDirective {
+    pub fn scheme(&self) -> Option<PagingScheme> {
+        self.scheme
+    }
+
+    pub fn field_values(&self, ctx: &eval::EvalContext) -> Result<Vec<(String, i64)>, CorkError> {
+        eval_fields(&self.fields, ctx)
+    }
+}
+
+fn eval_fields(fields: &[(String, Expr)], ctx: &eval::EvalContext) -> Result<Vec<(String, i64)>, CorkError> {
+    fields
+        .iter()
+        .map(|(name, expr)| Ok((name.clone(), eval::eval_expr_with(expr, ctx)?)))
+        .collect()
+}
+
+/// A ModuleDirective is a command of the form
+/// "module <name> = <base>[, <image base>[, <size>]]".
+#[derive(Debug, PartialEq, Eq)]
+pub struct ModuleDirective {
+    name: String,
+    base: Expr,
+    image_base: Option<Expr>,
+    size: Option<Expr>,
+}
+
+impl ModuleDirective {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// values evaluates the live base and, when given, the image base and
+    /// the size.
+    pub fn values(&self, ctx: &eval::EvalContext) -> Result<(i64, Option<i64>, Option<i64>), CorkError> {
+        let base = eval::eval_expr_with(&self.base, ctx)?;
+        let optional = |expr: &Option<Expr>| expr.as_ref().map(|expr| eval::eval_expr_with(expr, ctx)).transpose();
+        Ok((base, optional(&self.image_base)?, optional(&self.size)?))
+    }
+}
+
+/// A HashLookupDirective is a command of the form "hashlookup <algo> [expr]".
+/// Without an expression, `ans` is looked up.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HashLookupDirective {
+    algo: HashAlgo,
+    expr: Expr,
+}
+
+impl HashLookupDirective {
+    pub fn algo(&self) -> HashAlgo {
+        self.algo
+    }
+
+    pub fn value_with(&self, ctx: &eval::EvalContext) -> Result<i64, CorkError> {
+        eval::eval_expr_with(&self.expr, ctx)
+    }
+}
+
 /// A Command is a one line worth of input from the user.
 /// It can either be a SetDirective or an Expr.
 /// As an escape-hatch, there is also an empty command.
@@ -136,6 +340,20 @@ pub enum Command {
     Expr(Expr),
     Set(SetDirective),
     Convert(ConvDirective),
+    Bits(BitEdit),
+    Decode(DecodeDirective),
+    Flags(FlagsDirective),
+    Encode(EncodeDirective),
+    Va(VaDirective),
+    MkVa(MkVaDirective),
+    Module(ModuleDirective),
+    WhereIs(Expr),
+    WhatIs(Expr),
+    Cyclic(Expr),
+    HashLookup(HashLookupDirective),
+    XorBrute(Expr),
+    Modules,
+    History,
     Empty,
 }
 
@@ -157,9 +375,94 @@ pub fn parse_line<T: AsRef<str>>(line: T, config: &Config) -> Result<Command> {
     parse_comm(comm.unwrap().into_inner().next().unwrap())
 }
 
+/// prefixed_literal gives the length of the number with a radix prefix,
+/// like `0x10` or `0r36:zz`, that `text` starts with. Such numbers read the
+/// same in both modes.
+pub fn prefixed_literal(text: &str) -> Option<usize> {
+    [Rule::base_literal, Rule::hex_with_prefix, Rule::oct, Rule::bin, Rule::dec_with_prefix]
+        .into_iter()
+        .find_map(|rule| CommandParser::parse(rule, text).ok())
+        .and_then(|mut pairs| pairs.next())
+        .map(|pair| pair.as_str().len())
+}
+
 fn parse_comm(pair: Pair<Rule>) -> Result<Command> {
     match pair.as_rule() {
         Rule::expr_dec | Rule::expr_hex => Ok(Command::Expr(parse_expr(pair.into_inner())?)),
+        Rule::history_directive => Ok(Command::History),
+        Rule::modules_directive => Ok(Command::Modules),
+        Rule::module_directive_dec | Rule::module_directive_hex => {
+            let mut pairs = pair.into_inner();
+            let name = pairs.next().unwrap().as_str().to_string();
+            let base = parse_expr(Pairs::single(pairs.next().unwrap()))?;
+            let mut optional = || pairs.next().map(|expr_pair| parse_expr(Pairs::single(expr_pair))).transpose();
+            let image_base = optional()?;
+            let size = optional()?;
+            Ok(Command::Module(ModuleDirective { name, base, image_base, size }))
+        }
+        Rule::cyclic_directive_dec | Rule::cyclic_directive_hex => Ok(Command::Cyclic(parse_expr(Pairs::single(
+            pair.into_inner().next().unwrap(),
+        ))?)),
+        Rule::hashlookup_directive_dec | Rule::hashlookup_directive_hex => {
+            let mut pairs = pair.into_inner();
+            let algo = pairs.next().unwrap().as_str().parse()?;
+            let expr = match pairs.next() {
+                Some(expr_pair) => parse_expr(Pairs::single(expr_pair))?,
+                None => Expr::Ans,
+            };
+            Ok(Command::HashLookup(HashLookupDirective { algo, expr }))
+        }
+        Rule::xorbrute_directive_dec | Rule::xorbrute_directive_hex => Ok(Command::XorBrute(parse_expr(
+            Pairs::single(pair.into_inner().next().unwrap()),
+        )?)),
+        Rule::whereis_directive_dec | Rule::whereis_directive_hex => match pair.into_inner().next() {
+            Some(expr_pair) => Ok(Command::WhereIs(parse_expr(Pairs::single(expr_pair))?)),
+            None => Ok(Command::WhereIs(Expr::Ans)),
+        },
+        Rule::whatis_directive_dec | Rule::whatis_directive_hex => match pair.into_inner().next() {
+            Some(expr_pair) => Ok(Command::WhatIs(parse_expr(Pairs::single(expr_pair))?)),
+            None => Ok(Command::WhatIs(Expr::Ans)),
+        },
+        Rule::bits_directive => Ok(Command::Bits(parse_bit_edit(pair)?)),
+        Rule::decode_directive_dec | Rule::decode_directive_hex => {
+            let mut pairs = pair.into_inner();
+            let layout = pairs.next().unwrap().as_str().to_string();
+            let expr = match pairs.next() {
+                Some(expr_pair) => parse_expr(Pairs::single(expr_pair))?,
+                None => Expr::Ans,
+            };
+            Ok(Command::Decode(DecodeDirective { layout, expr }))
+        }
+        Rule::flags_directive_dec | Rule::flags_directive_hex => {
+            let mut pairs = pair.into_inner();
+            let table = pairs.next().unwrap().as_str().parse()?;
+            let expr = match pairs.next() {
+                Some(expr_pair) => parse_expr(Pairs::single(expr_pair))?,
+                None => Expr::Ans,
+            };
+            Ok(Command::Flags(FlagsDirective { table, expr }))
+        }
+        Rule::encode_directive_dec | Rule::encode_directive_hex => {
+            let mut pairs = pair.into_inner();
+            let layout = pairs.next().unwrap().as_str().to_string();
+            let fields = parse_field_assigns(pairs)?;
+            Ok(Command::Encode(EncodeDirective { layout, fields }))
+        }
+        Rule::va_directive_dec | Rule::va_directive_hex => {
+            let mut pairs = pair.into_inner().peekable();
+            let scheme = parse_paging_scheme(&mut pairs)?;
+            let expr = match pairs.next() {
+                Some(expr_pair) => parse_expr(Pairs::single(expr_pair))?,
+                None => Expr::Ans,
+            };
+            Ok(Command::Va(VaDirective { scheme, expr }))
+        }
+        Rule::mkva_directive_dec | Rule::mkva_directive_hex => {
+            let mut pairs = pair.into_inner().peekable();
+            let scheme = parse_paging_scheme(&mut pairs)?;
+            let fields = parse_field_assigns(pairs)?;
+            Ok(Command::MkVa(MkVaDirective { scheme, fields }))
+        }
         Rule::set_directive => Ok(Command::Set(SetDirective {
             args: pair.as_str().split(' ').skip(1).map(String::from).collect(),
         })),
@@ -169,10 +472,13 @@ fn parse_comm(pair: Pair<Rule>) -> Result<Command> {
             let radix_pair = pairs.next().unwrap();
             Ok(Command::Convert(ConvDirective {
                 expr: parse_expr(expr_pair.into_inner())?,
-                radix: parse_radix(radix_pair),
+                radix: parse_radix(radix_pair)?,
+                explicit: true,
+                unsigned: false,
             }))
         }
         Rule::convert_directive_dec | Rule::convert_directive_hex => {
+            let unsigned = !pair.as_str().starts_with('-');
             match parse_expr(pair.into_inner())? {
                 Expr::Num(num, radix)  => {
                     match radix {
@@ -180,18 +486,24 @@ fn parse_comm(pair: Pair<Rule>) -> Result<Command> {
                             Ok(Command::Convert(ConvDirective {
                                 expr: Expr::Num(num, radix),
                                 radix: FormatRadix::Hex,
+                                explicit: false,
+                                unsigned,
                             }))
                         },
                         Radix::HexWithPrefix | Radix::Hex => {
                             Ok(Command::Convert(ConvDirective {
                                 expr: Expr::Num(num, radix),
                                 radix: FormatRadix::Decimal,
+                                explicit: false,
+                                unsigned,
                             }))
                         },
                         _ => {
                             Ok(Command::Convert(ConvDirective {
                                 expr: Expr::Num(num, radix),
                                 radix: FormatRadix::Decimal,
+                                explicit: false,
+                                unsigned,
                             }))
                         },
                     }
@@ -227,6 +539,8 @@ pub enum Radix {
     DecWithPrefix,
     Hex,
     HexWithPrefix,
+    /// A `0r` literal, in any radix or alphabet.
+    Base,
 }
 
 impl Radix {
@@ -238,18 +552,13 @@ impl Radix {
             Radix::DecWithPrefix => 10,
             Radix::Hex => 16,
             Radix::HexWithPrefix => 16,
+            Radix::Base => unreachable!("0r literals are read by parse_base_literal"),
         }
     }
 }
 
-fn parse_radix(p: Pair<Rule>) -> FormatRadix {
-    match p.as_str() {
-        "dec" => FormatRadix::Decimal,
-        "oct" => FormatRadix::Octal,
-        "hex" => FormatRadix::Hex,
-        "bin" => FormatRadix::Binary,
-        _ => unreachable!(),
-    }
+fn parse_radix(p: Pair<Rule>) -> Result<FormatRadix, CorkError> {
+    p.as_str().parse()
 }
 
 fn parse_num(mut s: &str, radix: Radix) -> Result<i64, ParseIntError> {
@@ -264,8 +573,30 @@ fn parse_num(mut s: &str, radix: Radix) -> Result<i64, ParseIntError> {
     }
     // Remove any underscores for readability
     let num_str = s.replace('_', "");
-    i64::from_str_radix(&num_str, radix.numeric_radix())
-        .map(|n| if negative { -n } else { n })
+    // Non-decimal numbers are bit patterns, so the full 64 bits are accepted and
+    // read back as two's complement (0xffff_ffff_ffff_ffff is -1)
+    let num = match radix.numeric_radix() {
+        10 => num_str.parse::<i64>()?,
+        numeric_radix => u64::from_str_radix(&num_str, numeric_radix)? as i64,
+    };
+    Ok(if negative { num.wrapping_neg() } else { num })
+}
+
+/// parse_base_literal reads a `0r36:zz` literal, whose radix may also be
+/// the name of an alphabet. Like the other prefixed numbers it is a bit
+/// pattern, so the full 64 bits are accepted.
+fn parse_base_literal(mut s: &str) -> Result<i64, CorkError> {
+    let negative = s.starts_with('-');
+    if negative {
+        s = &s[1..];
+    }
+    let (base, digits) = s[2..].split_once(':').expect("the grammar requires a colon");
+    let num = match base.parse::<u32>() {
+        Ok(radix) => u64::from_str_radix(&digits.replace('_', ""), encoding::check_radix(radix)?)
+            .map_err(|err| CorkError::Eval(format!("{} is not a base {} number: {}", digits, radix, err)))?,
+        Err(_) => base.parse::<Alphabet>()?.decode(digits)?,
+    } as i64;
+    Ok(if negative { num.wrapping_neg() } else { num })
 }
 
 fn parse_expr(expression: Pairs<Rule>) -> Result<Expr> {
@@ -290,7 +621,20 @@ fn parse_expr(expression: Pairs<Rule>) -> Result<Expr> {
             Rule::bin => parse_num(primary.as_str(), Radix::Bin)
                 .with_context(|| format!("failed to parse binary number: {}", primary.as_str()))
                 .map(|num| Expr::Num(num, Radix::Bin)),
+            Rule::base_literal => Ok(Expr::Num(parse_base_literal(primary.as_str())?, Radix::Base)),
             Rule::ans => Ok(Expr::Ans),
+            Rule::hist_ref => primary.as_str()[1..]
+                .parse()
+                .with_context(|| format!("failed to parse history reference: {}", primary.as_str()))
+                .map(Expr::Hist),
+            Rule::call_dec | Rule::call_hex => parse_call(primary),
+            Rule::ident => Ok(Expr::Name(primary.as_str().to_string())),
+            Rule::string => parse_string(primary.as_str())
+                .with_context(|| format!("failed to parse string: {}", primary.as_str()))
+                .map(Expr::Str),
+            Rule::ipv4 => Ok(Expr::Ipv4(net::parse_ipv4(primary.as_str())?)),
+            Rule::ipv6 => Ok(Expr::Ipv6(net::parse_ipv6(primary.as_str())?)),
+            Rule::flag => Ok(Expr::Flag(primary.as_str().to_string())),
             Rule::expr_dec | Rule::expr_hex => parse_expr(primary.into_inner()),
             rule => unreachable!("parse_expr expected atom, found {:?}", rule),
         })
@@ -319,42 +663,580 @@ fn parse_expr(expression: Pairs<Rule>) -> Result<Expr> {
         .parse(expression)
 }
 
+fn parse_bit_edit(pair: Pair<Rule>) -> Result<BitEdit> {
+    let mut pairs = pair.into_inner();
+    let op = pairs.next().unwrap();
+    let arg = pairs.next();
+    match op.as_rule() {
+        Rule::bit_op => {
+            let mut bounds = arg.unwrap().into_inner().map(|p| p.as_str().parse::<u32>());
+            let lo = bounds.next().unwrap().context("failed to parse bit index")?;
+            let hi = match bounds.next() {
+                Some(hi) => hi.context("failed to parse bit index")?,
+                None => lo,
+            };
+            Ok(match op.as_str() {
+                "flip" | "flip-bits" => BitEdit::Flip(lo, hi),
+                "set-bit" | "set-bits" => BitEdit::Set(lo, hi),
+                "clear-bit" | "clear-bits" => BitEdit::Clear(lo, hi),
+                op => unreachable!("unexpected bit operation {}", op),
+            })
+        }
+        Rule::shift_op => {
+            let n = match arg {
+                Some(n) => n.as_str().parse().context("failed to parse shift amount")?,
+                None => 1,
+            };
+            Ok(if op.as_str() == "shl" { BitEdit::Shl(n) } else { BitEdit::Shr(n) })
+        }
+        rule => unreachable!("expected bit operation, found {:?}", rule),
+    }
+}
+
+/// parse_string reads a quoted string literal into its UTF-8 bytes, with
+/// the escapes `\\`, `\"`, `\'`, `\n`, `\r`, `\t`, `\0` and `\xNN`.
+fn parse_string(literal: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut chars = literal[1..literal.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('\\') => b'\\',
+            Some('"') => b'"',
+            Some('\'') => b'\'',
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('0') => 0,
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() == 2)
+                    .with_context(|| format!("invalid escape \\x{}", digits))?
+            }
+            Some(c) => anyhow::bail!("invalid escape \\{}", c),
+            None => anyhow::bail!("unfinished escape"),
+        };
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+fn parse_field_assigns<'a>(assigns: impl Iterator<Item = Pair<'a, Rule>>) -> Result<Vec<(String, Expr)>> {
+    assigns
+        .map(|assign| {
+            let mut pairs = assign.into_inner();
+            let name = pairs.next().unwrap().as_str().to_string();
+            Ok((name, parse_expr(Pairs::single(pairs.next().unwrap()))?))
+        })
+        .collect()
+}
+
+fn parse_paging_scheme<'a>(
+    pairs: &mut Peekable<impl Iterator<Item = Pair<'a, Rule>>>,
+) -> Result<Option<PagingScheme>> {
+    match pairs.next_if(|pair| pair.as_rule() == Rule::paging_scheme) {
+        Some(pair) => Ok(Some(pair.as_str().parse()?)),
+        None => Ok(None),
+    }
+}
+
+fn parse_call(call: Pair<Rule>) -> Result<Expr> {
+    let mut pairs = call.into_inner();
+    let name = pairs.next().unwrap().as_str().to_string();
+    let args = pairs
+        .map(|arg| match arg.as_rule() {
+            Rule::expr_dec | Rule::expr_hex => parse_expr(arg.into_inner()),
+            _ => parse_expr(Pairs::single(arg)),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Expr::Call(name, args))
+}
+
 pub mod eval {
     use super::*;
+    use crate::modules::Module;
+    use crate::unicode;
+
+    /// A Value is what an expression evaluates to. Most are numbers, but the
+    /// encoding functions give back raw bytes or encoded text, and IPv6
+    /// addresses are wider than a number.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Value {
+        Int(i64),
+        Bytes(Vec<u8>),
+        Text(String),
+        Ipv6(u128),
+    }
+
+    impl Value {
+        /// int reads bytes and text back as a number in the current byte
+        /// order, so a decoded value round-trips to the number encoded.
+        pub fn int(self, ctx: &EvalContext) -> Result<i64, CorkError> {
+            match self {
+                Value::Int(value) => Ok(value),
+                Value::Bytes(bytes) => format::from_bytes(&bytes, ctx.endian),
+                Value::Text(text) => format::from_bytes(text.as_bytes(), ctx.endian),
+                Value::Ipv6(addr) => u64::try_from(addr).map(|addr| addr as i64).map_err(|_| {
+                    CorkError::Eval(format!("{} does not fit in 64 bits", net::fmt_ipv6(addr)))
+                }),
+            }
+        }
+
+        /// into_bytes lays a number out in the current width and byte order.
+        pub fn into_bytes(self, ctx: &EvalContext) -> Vec<u8> {
+            match self {
+                Value::Int(value) => ctx.bytes(value),
+                Value::Bytes(bytes) => bytes,
+                Value::Text(text) => text.into_bytes(),
+                // addresses are laid out in network byte order
+                Value::Ipv6(addr) => addr.to_be_bytes().to_vec(),
+            }
+        }
+
+        /// into_key is like into_bytes, but a number only keeps the bytes
+        /// it needs, so `0x41` is the single byte `A`.
+        fn into_key(self, ctx: &EvalContext) -> Vec<u8> {
+            match self {
+                Value::Int(value) => {
+                    let mut bytes = ctx.bytes(value);
+                    let used = (64 - (value as u64).leading_zeros()).div_ceil(8).max(1) as usize;
+                    let len = used.min(bytes.len());
+                    match ctx.endian {
+                        Endian::Little => bytes.truncate(len),
+                        Endian::Big => {
+                            bytes.drain(..bytes.len() - len);
+                        }
+                    }
+                    bytes
+                }
+                value => value.into_bytes(ctx),
+            }
+        }
+    }
+
+    impl fmt::Display for Value {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Value::Int(value) => write!(f, "{}", value),
+                Value::Bytes(bytes) => write!(f, "\"{}\"", format::fmt_bytes(bytes)),
+                Value::Text(text) => write!(f, "{}", text),
+                Value::Ipv6(addr) => write!(f, "{}", net::fmt_ipv6(*addr)),
+            }
+        }
+    }
+
+    /// An EvalContext holds everything an expression can refer to besides literals.
+    pub struct EvalContext<'a> {
+        ans: i64,
+        history: Option<&'a History>,
+        modules: Option<&'a Modules>,
+        width: u32,
+        endian: Endian,
+        cyclic: Option<&'a Cyclic>,
+    }
+
+    impl<'a> EvalContext<'a> {
+        pub fn new(ans: i64) -> Self {
+            EvalContext {
+                ans,
+                history: None,
+                modules: None,
+                width: 64,
+                endian: Endian::Little,
+                cyclic: None,
+            }
+        }
+
+        pub fn with_history(mut self, history: &'a History) -> Self {
+            self.history = Some(history);
+            self
+        }
+
+        pub fn with_modules(mut self, modules: &'a Modules) -> Self {
+            self.modules = Some(modules);
+            self
+        }
+
+        /// with_bytes sets how values are laid out when read as bytes.
+        pub fn with_bytes(mut self, width: u32, endian: Endian) -> Self {
+            self.width = width;
+            self.endian = endian;
+            self
+        }
+
+        /// with_cyclic sets the pattern `cyclic_find` looks values up in.
+        pub fn with_cyclic(mut self, cyclic: &'a Cyclic) -> Self {
+            self.cyclic = Some(cyclic);
+            self
+        }
+
+        fn bytes(&self, value: i64) -> Vec<u8> {
+            format::to_bytes(value, self.width, self.endian)
+        }
+
+        fn module(&self, name: &str) -> Result<&'a Module, CorkError> {
+            match self.modules {
+                Some(modules) => modules.get(name),
+                None => Err(CorkError::UnknownModule(name.to_string())),
+            }
+        }
+
+        fn hist(&self, id: u64) -> Result<i64, CorkError> {
+            match self.history {
+                Some(history) => Ok(history.get(id)?.result()),
+                None => Err(CorkError::NoHistoryEntry(id)),
+            }
+        }
+    }
 
     pub fn eval_expr(expr: &Expr, ans: i64) -> Result<i64, CorkError> {
+        eval_expr_with(expr, &EvalContext::new(ans))
+    }
+
+    pub fn eval_expr_with(expr: &Expr, ctx: &EvalContext) -> Result<i64, CorkError> {
+        eval_value(expr, ctx)?.int(ctx)
+    }
+
+    /// eval_value evaluates an expression without turning bytes or text
+    /// into a number.
+    pub fn eval_value(expr: &Expr, ctx: &EvalContext) -> Result<Value, CorkError> {
+        match &expr {
+            Expr::Call(name, args) => call_function(name, args, ctx),
+            Expr::Str(bytes) => Ok(Value::Bytes(bytes.clone())),
+            Expr::Ipv6(addr) => Ok(Value::Ipv6(*addr)),
+            Expr::BinOp(binop) => match (eval_value(&binop.left, ctx)?, eval_value(&binop.right, ctx)?) {
+                // `^` also xors byte sequences, repeating the other operand as the key
+                (data @ (Value::Bytes(_) | Value::Text(_)), key) | (key, data @ (Value::Bytes(_) | Value::Text(_)))
+                    if binop.op == Op::Xor =>
+                {
+                    Ok(Value::Bytes(encoding::xor_bytes(&data.into_bytes(ctx), &key.into_key(ctx))?))
+                }
+                // an IPv6 address keeps its 128 bits, the other operand is a number
+                (Value::Ipv6(left), Value::Ipv6(right)) => ipv6_op(&binop.op, left, right),
+                (Value::Ipv6(left), right) => ipv6_op(&binop.op, left, right.int(ctx)? as u64 as u128),
+                (left, Value::Ipv6(right)) => ipv6_op(&binop.op, left.int(ctx)? as u64 as u128, right),
+                (left, right) => int_op(&binop.op, left.int(ctx)?, right.int(ctx)?).map(Value::Int),
+            },
+            _ => eval_int(expr, ctx).map(Value::Int),
+        }
+    }
+
+    fn int_op(op: &Op, left: i64, right: i64) -> Result<i64, CorkError> {
+        match op {
+            // note that order does not matter here
+            Op::Add => Ok(left + right),
+            Op::Sub => Ok(left - right),
+            Op::Mul => Ok(left * right),
+            Op::And => Ok(left & right),
+            Op::Xor => Ok(left ^ right),
+            Op::Or => Ok(left | right),
+            Op::LShift => Ok(left << right),
+            Op::RShift => Ok(left >> right),
+            Op::Div => {
+                if right == 0 {
+                    Err(CorkError::Eval(String::from("Cannot divide by 0")))
+                } else {
+                    Ok(left / right)
+                }
+            }
+            Op::Rem => {
+                if right == 0 {
+                    Err(CorkError::Eval(String::from("Cannot divide by 0")))
+                } else {
+                    Ok(left % right)
+                }
+            }
+        }
+    }
+
+    /// ipv6_op is the arithmetic that makes sense on addresses: stepping
+    /// through them and masking them. Sums wrap around the address space.
+    fn ipv6_op(op: &Op, left: u128, right: u128) -> Result<Value, CorkError> {
+        match op {
+            Op::Add => Ok(Value::Ipv6(left.wrapping_add(right))),
+            Op::Sub => Ok(Value::Ipv6(left.wrapping_sub(right))),
+            Op::And => Ok(Value::Ipv6(left & right)),
+            Op::Or => Ok(Value::Ipv6(left | right)),
+            Op::Xor => Ok(Value::Ipv6(left ^ right)),
+            _ => Err(CorkError::Eval(String::from("IPv6 addresses only take + - & | ^"))),
+        }
+    }
+
+    fn eval_int(expr: &Expr, ctx: &EvalContext) -> Result<i64, CorkError> {
         match &expr {
             Expr::Num(num, _) => Ok(*num),
-            Expr::BinOp(expr) => {
-                let left = eval_expr(expr.left.as_ref(), ans)?;
-                let right = eval_expr(expr.right.as_ref(), ans)?;
-                match expr.op {
-                    // note that order does not matter here
-                    Op::Add => Ok(left + right),
-                    Op::Sub => Ok(left - right),
-                    Op::Mul => Ok(left * right),
-                    Op::And => Ok(left & right),
-                    Op::Xor => Ok(left ^ right),
-                    Op::Or => Ok(left | right),
-                    Op::LShift => Ok(left << right),
-                    Op::RShift => Ok(left >> right),
-                    Op::Div => {
-                        if right == 0 {
-                            Err(CorkError::Eval(String::from("Cannot divide by 0")))
-                        } else {
-                            Ok(left / right)
-                        }
+            Expr::Ipv4(addr) => Ok(*addr as i64),
+            Expr::Flag(name) => flags::lookup(name),
+            Expr::Call(..) | Expr::Str(_) | Expr::Ipv6(_) | Expr::BinOp(_) => eval_expr_with(expr, ctx),
+            Expr::Hist(id) => ctx.hist(*id),
+            // names only appear as function arguments, where a module name
+            // stands for its live base, like in `hexenc(ntdll)`
+            Expr::Name(name) => Ok(ctx.module(name)?.base()),
+            Expr::Ans => Ok(ctx.ans),
+        }
+    }
+
+    fn call_function(name: &str, args: &[Expr], ctx: &EvalContext) -> Result<Value, CorkError> {
+        match name {
+            "b64enc" | "hexenc" | "cstr" | "urlenc" => {
+                let bytes = bytes_arg(&expect_args::<1>(name, args)?[0], ctx)?;
+                Ok(Value::Text(match name {
+                    "b64enc" => encoding::b64_encode(&bytes),
+                    "hexenc" => encoding::hex_encode(&bytes),
+                    "cstr" => encoding::cstr_escape(&bytes),
+                    _ => encoding::url_encode(&bytes),
+                }))
+            }
+            "b64dec" | "hexdec" | "urldec" => {
+                let text = bytes_arg(&expect_args::<1>(name, args)?[0], ctx)?;
+                Ok(Value::Bytes(match name {
+                    "b64dec" => encoding::b64_decode(&text)?,
+                    "hexdec" => encoding::hex_decode(&text)?,
+                    _ => encoding::url_decode(&text)?,
+                }))
+            }
+            "uleb128" | "varint" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(Value::Bytes(encoding::uleb128_encode(value as u64)))
+            }
+            "sleb128" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(Value::Bytes(encoding::sleb128_encode(value)))
+            }
+            "utf8" | "utf16le" => {
+                let text = text_arg(&expect_args::<1>(name, args)?[0], ctx)?;
+                Ok(Value::Bytes(match name {
+                    "utf8" => text.into_bytes(),
+                    _ => unicode::encode_utf16le(&text),
+                }))
+            }
+            "utf8dec" | "utf16ledec" => {
+                let bytes = bytes_arg(&expect_args::<1>(name, args)?[0], ctx)?;
+                let text = match name {
+                    "utf8dec" => unicode::decode_utf8(&bytes)?,
+                    _ => unicode::decode_utf16le(&bytes)?,
+                };
+                Ok(Value::Text(unicode::code_points(&text)))
+            }
+            "xor" => {
+                let [data, key] = expect_args::<2>(name, args)?;
+                let data = bytes_arg(data, ctx)?;
+                let key = eval_value(key, ctx)?.into_key(ctx);
+                Ok(Value::Bytes(encoding::xor_bytes(&data, &key)?))
+            }
+            // an IPv6 address keeps its family, anything else is an IPv4 address
+            "network" | "broadcast" => {
+                let [addr, prefix] = expect_args::<2>(name, args)?;
+                let prefix = prefix_arg(prefix, ctx)?;
+                match eval_value(addr, ctx)? {
+                    Value::Ipv6(addr) => {
+                        let mask = net::netmask6(prefix)?;
+                        Ok(Value::Ipv6(if name == "network" { addr & mask } else { addr | !mask }))
                     }
-                    Op::Rem => {
-                        if right == 0 {
-                            Err(CorkError::Eval(String::from("Cannot divide by 0")))
-                        } else {
-                            Ok(left % right)
-                        }
+                    addr => {
+                        let addr = net::ipv4(addr.int(ctx)?)?;
+                        let mask = net::netmask(prefix)?;
+                        Ok(Value::Int(if name == "network" { addr & mask } else { addr | !mask } as i64))
                     }
                 }
             }
-            Expr::Ans => Ok(ans),
+            _ => call_int_function(name, args, ctx).map(Value::Int),
+        }
+    }
+
+    fn call_int_function(name: &str, args: &[Expr], ctx: &EvalContext) -> Result<i64, CorkError> {
+        match name {
+            "hist" => {
+                let [id] = int_args::<1>(name, args, ctx)?;
+                ctx.hist(id as u64)
+            }
+            "cyclic_find" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                let bytes = ctx.bytes(value);
+                let offset = match ctx.cyclic {
+                    Some(cyclic) => cyclic.find(&bytes)?,
+                    None => Cyclic::default().find(&bytes)?,
+                };
+                Ok(offset as i64)
+            }
+            "uleb128dec" | "varintdec" => {
+                let bytes = bytes_arg(&expect_args::<1>(name, args)?[0], ctx)?;
+                Ok(encoding::uleb128_decode(&bytes)? as i64)
+            }
+            "sleb128dec" => encoding::sleb128_decode(&bytes_arg(&expect_args::<1>(name, args)?[0], ctx)?),
+            "zigzag" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::zigzag(value) as i64)
+            }
+            "unzigzag" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::unzigzag(value as u64))
+            }
+            "gray" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::gray(value as u64) as i64)
+            }
+            "ungray" => {
+                let [code] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::ungray(code as u64) as i64)
+            }
+            "bcd" | "bcd_unpacked" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::bcd_encode(value, name == "bcd")? as i64)
+            }
+            "unbcd" | "unbcd_unpacked" => {
+                let [code] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::bcd_decode(code as u64, name == "unbcd")? as i64)
+            }
+            "excess" | "unexcess" => {
+                // without a K, the offset binary bias of the current width is used
+                let [value, k] = match args {
+                    [value] => [eval_expr_with(value, ctx)?, 1 << (ctx.width - 1)],
+                    _ => int_args::<2>(name, args, ctx)?,
+                };
+                match name {
+                    "excess" => Ok(encoding::excess_encode(value, k as u64, ctx.width)? as i64),
+                    _ => encoding::excess_decode(bits::to_unsigned(value, ctx.width), k as u64),
+                }
+            }
+            "ones" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::ones_encode(value, ctx.width)? as i64)
+            }
+            "unones" => {
+                let [code] = int_args::<1>(name, args, ctx)?;
+                Ok(encoding::ones_decode(code as u64, ctx.width))
+            }
+            "unix" | "unix_ms" | "unix_ns" | "filetime" | "mactime" | "dostime" => {
+                let epoch: Epoch = name.parse()?;
+                let (secs, nanos) = match eval_value(&expect_args::<1>(name, args)?[0], ctx)? {
+                    Value::Int(secs) => (secs, 0),
+                    value => timestamp::parse_iso(&unicode::decode_utf8(&value.into_bytes(ctx))?)?,
+                };
+                epoch.encode(secs, nanos)
+            }
+            "netmask" => Ok(net::netmask(prefix_arg(&expect_args::<1>(name, args)?[0], ctx)?)? as i64),
+            "hosts" => Ok(net::hosts(prefix_arg(&expect_args::<1>(name, args)?[0], ctx)?)? as i64),
+            // the kernel's GENMASK takes the high bit first
+            "mask" | "genmask" | "genmask_ull" => {
+                let [hi, lo] = int_args::<2>(name, args, ctx)?;
+                Ok(bits::genmask(hi, lo)? as i64)
+            }
+            "bits" | "bit" | "bit_ull" => {
+                if name != "bits" {
+                    expect_args::<1>(name, args)?;
+                }
+                let positions = args
+                    .iter()
+                    .map(|arg| eval_expr_with(arg, ctx))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(bits::bit_list(&positions)? as i64)
+            }
+            "rel32" => {
+                let [from, to, len] = match args {
+                    [from, to] => [eval_expr_with(from, ctx)?, eval_expr_with(to, ctx)?, 5],
+                    _ => int_args::<3>(name, args, ctx)?,
+                };
+                insn::rel32(from, to, len)
+            }
+            "adrp" => {
+                let [from, to] = int_args::<2>(name, args, ctx)?;
+                insn::adrp(from, to)
+            }
+            "b" | "bl" => {
+                let [from, to] = int_args::<2>(name, args, ctx)?;
+                Ok(insn::encode_b(from, to, name == "bl")? as i64)
+            }
+            "bdec" => {
+                let [word, at] = int_args::<2>(name, args, ctx)?;
+                insn::decode_b(insn::word(word)?, at)
+            }
+            "armimm" => {
+                let [value] = int_args::<1>(name, args, ctx)?;
+                insn::armimm(insn::word(value)?)
+                    .map(|encoded| encoded as i64)
+                    .ok_or_else(|| CorkError::Eval(format!("{:#x} is not an ARM modified immediate", value)))
+            }
+            "armimmdec" => {
+                let [encoded] = int_args::<1>(name, args, ctx)?;
+                Ok(insn::armimm_decode(insn::word(encoded)?)? as i64)
+            }
+            "cyclic" => Err(CorkError::Eval("cyclic(n) prints a pattern, it has no value".to_string())),
+            "rebase" | "rva" => {
+                let [addr, module] = expect_args::<2>(name, args)?;
+                let addr = eval_expr_with(addr, ctx)?;
+                let module = ctx.module(module_arg(name, module)?)?;
+                Ok(if name == "rebase" { module.rebase(addr) } else { module.rva(addr) })
+            }
+            _ => match name.parse::<HashAlgo>() {
+                Ok(algo) => {
+                    let (data, seed) = match (algo, args) {
+                        (HashAlgo::Murmur3_32, [data, seed]) => (data, eval_expr_with(seed, ctx)? as u32),
+                        _ => (&expect_args::<1>(name, args)?[0], 0),
+                    };
+                    Ok(algo.hash(&bytes_arg(data, ctx)?, seed) as i64)
+                }
+                Err(_) => Err(CorkError::Eval(format!("unknown function {}", name))),
+            },
+        }
+    }
+
+    /// bytes_arg gives the bytes of a string literal or of a byte or text
+    /// value, or those of a number laid out in the current width and byte
+    /// order.
+    fn bytes_arg(arg: &Expr, ctx: &EvalContext) -> Result<Vec<u8>, CorkError> {
+        Ok(eval_value(arg, ctx)?.into_bytes(ctx))
+    }
+
+    /// text_arg gives the text of a string or byte value, or the character
+    /// a number is the code point of.
+    fn text_arg(arg: &Expr, ctx: &EvalContext) -> Result<String, CorkError> {
+        match eval_value(arg, ctx)? {
+            Value::Int(value) => Ok(unicode::to_char(value)?.to_string()),
+            value => unicode::decode_utf8(&value.into_bytes(ctx)),
+        }
+    }
+
+    fn int_args<const N: usize>(name: &str, args: &[Expr], ctx: &EvalContext) -> Result<[i64; N], CorkError> {
+        let mut values = [0; N];
+        for (value, arg) in values.iter_mut().zip(expect_args::<N>(name, args)?) {
+            *value = eval_expr_with(arg, ctx)?;
         }
+        Ok(values)
+    }
+
+    /// prefix_arg reads the length of a CIDR prefix. Prefix lengths are
+    /// written in decimal, so a bare number is decimal in hex mode too.
+    fn prefix_arg(arg: &Expr, ctx: &EvalContext) -> Result<i64, CorkError> {
+        match arg {
+            Expr::Num(num, Radix::Hex) => format!("{:x}", num)
+                .parse()
+                .map_err(|_| CorkError::Eval(format!("{:x} is not a decimal prefix length", num))),
+            _ => eval_expr_with(arg, ctx),
+        }
+    }
+
+    fn module_arg<'e>(name: &str, arg: &'e Expr) -> Result<&'e str, CorkError> {
+        match arg {
+            Expr::Name(module) => Ok(module),
+            _ => Err(CorkError::Eval(format!("{} expects a module name", name))),
+        }
+    }
+
+    fn expect_args<'e, const N: usize>(name: &str, args: &'e [Expr]) -> Result<&'e [Expr; N], CorkError> {
+        args.try_into().map_err(|_| {
+            CorkError::Eval(format!(
+                "{} expects {} argument(s), found {}",
+                name,
+                N,
+                args.len()
+            ))
+        })
     }
 }
\ No newline at end of file
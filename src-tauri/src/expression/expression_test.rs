@@ -204,6 +204,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Hex,
+        explicit: true,
+        unsigned: false,
     };
     assert_eq!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv1));
 
@@ -218,6 +220,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Decimal,
+        explicit: true,
+        unsigned: false,
     };
     assert_ne!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv2));
 
@@ -232,6 +236,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Octal,
+        explicit: true,
+        unsigned: false,
     };
     assert_ne!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv3));
 
@@ -246,6 +252,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Binary,
+        explicit: true,
+        unsigned: false,
     };
     assert_ne!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv4));
 
@@ -261,6 +269,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Hex,
+        explicit: true,
+        unsigned: false,
     };
     assert_eq!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv1));
 
@@ -275,6 +285,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Decimal,
+        explicit: true,
+        unsigned: false,
     };
     assert_ne!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv2));
 
@@ -289,6 +301,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Octal,
+        explicit: true,
+        unsigned: false,
     };
     assert_ne!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv3));
 
@@ -303,6 +317,8 @@ fn test_convert_parse() {
             op: Op::Mul,
         }),
         radix: FormatRadix::Binary,
+        explicit: true,
+        unsigned: false,
     };
     assert_ne!(parse_line(conv_str, &config).unwrap(), Command::Convert(conv4));
 }
@@ -455,7 +471,7 @@ fn test_convert_output() {
                     .with_format_radix(conversion.radix())
                     .fmt(conversion.value(0).unwrap())
             );
-            assert_eq!(result, "\"0b111_1111\"");
+            assert_eq!(result, "\"0b1111111\"");
         }
         _ => panic!("Should have parsed to a conversion"),
     };
@@ -496,17 +512,17 @@ fn hex_parse() {
     let hex_str1 = "0x1a";
     assert_eq!(
         parse_line(hex_str1, &config).unwrap(), 
-        Command::Convert(ConvDirective { expr: Expr::Num(26, Radix::HexWithPrefix), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(26, Radix::HexWithPrefix), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let hex_str2 = "0xCAFE";
     assert_eq!(
         parse_line(hex_str2, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(51966, Radix::HexWithPrefix), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(51966, Radix::HexWithPrefix), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let hex_str3 = "0xFACE_A0CE";
     assert_eq!(
         parse_line(hex_str3, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(4207845582, Radix::HexWithPrefix), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(4207845582, Radix::HexWithPrefix), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
 }
 
@@ -516,17 +532,17 @@ fn oct_parse() {
     let oct_str1 = "0o345";
     assert_eq!(
         parse_line(oct_str1, &config).unwrap(), 
-        Command::Convert(ConvDirective { expr: Expr::Num(229, Radix::Oct), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(229, Radix::Oct), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let oct_str2 = "0o1232344";
     assert_eq!(
         parse_line(oct_str2, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(341220, Radix::Oct), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(341220, Radix::Oct), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let oct_str3 = "0o1232_34_4";
     assert_eq!(
         parse_line(oct_str3, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(341220, Radix::Oct), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(341220, Radix::Oct), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
 }
 
@@ -536,17 +552,17 @@ fn bin_parse() {
     let bin_str1 = "0b1010";
     assert_eq!(
         parse_line(bin_str1, &config).unwrap(), 
-        Command::Convert(ConvDirective { expr: Expr::Num(10, Radix::Bin), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(10, Radix::Bin), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let bin_str1 = "0b10100101";
     assert_eq!(
         parse_line(bin_str1, &config).unwrap(), 
-        Command::Convert(ConvDirective { expr: Expr::Num(165, Radix::Bin), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(165, Radix::Bin), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let bin_str3 = "0b10_10_01____01";
     assert_eq!(
         parse_line(bin_str3, &config).unwrap(), 
-        Command::Convert(ConvDirective { expr: Expr::Num(165, Radix::Bin), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(165, Radix::Bin), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
 }
 
@@ -557,24 +573,665 @@ fn dec_parse() {
     let dec_str1 = "1234_5678";
     assert_eq!(
         parse_line(dec_str1, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(12345678, Radix::Dec), radix: FormatRadix::Hex })
+        Command::Convert(ConvDirective { expr: Expr::Num(12345678, Radix::Dec), radix: FormatRadix::Hex, explicit: false, unsigned: true })
     );
     let dec_str2 = "0d1234_5678";
     assert_eq!(
         parse_line(dec_str2, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(12345678, Radix::DecWithPrefix), radix: FormatRadix::Hex })
+        Command::Convert(ConvDirective { expr: Expr::Num(12345678, Radix::DecWithPrefix), radix: FormatRadix::Hex, explicit: false, unsigned: true })
     );
 
     config.set_mode("hex".to_string());
     let dec_str1 = "1234_5678";
     assert_eq!(
         parse_line(dec_str1, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(305419896, Radix::Hex), radix: FormatRadix::Decimal })
+        Command::Convert(ConvDirective { expr: Expr::Num(305419896, Radix::Hex), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
     );
     let dec_str2 = "0d1234_5678";
     assert_eq!(
         parse_line(dec_str2, &config).unwrap(),
-        Command::Convert(ConvDirective { expr: Expr::Num(12345678, Radix::DecWithPrefix), radix: FormatRadix::Hex })
+        Command::Convert(ConvDirective { expr: Expr::Num(12345678, Radix::DecWithPrefix), radix: FormatRadix::Hex, explicit: false, unsigned: true })
     );
 }
 
+
+#[test]
+fn test_history_reference_eval() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let mut history = History::default();
+    history.record("a + 5", 0xf, FormatRadix::Hex, "hex");
+    history.record("ff", 0xff, FormatRadix::Decimal, "hex");
+    let ctx = EvalContext::new(1).with_history(&history);
+
+    let cases = [("$2", 0xff), ("$1 + hist(2)", 0x10e), ("hist($1 - d) * 2", 0x1fe), ("ans + $1", 0x10)];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr_with(&expr, &ctx).unwrap(), expected),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+
+    match parse_line("$3 to dec", &config).unwrap() {
+        Command::Convert(conversion) => {
+            assert_eq!(conversion.value_with(&ctx), Err(CorkError::NoHistoryEntry(3)))
+        }
+        _ => panic!("Should have parsed to a conversion"),
+    }
+    assert_eq!(parse_line("history", &config).unwrap(), Command::History);
+    // without parentheses an identifier is still a hex number
+    assert_eq!(
+        parse_line("cafe", &config).unwrap(),
+        Command::Convert(ConvDirective { expr: Expr::Num(0xcafe, Radix::Hex), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
+    );
+}
+
+#[test]
+fn test_bits_directive_parse() {
+    let mut config: Config = Config::new();
+    for mode in ["hex", "dec"] {
+        config.set_mode(mode.to_string());
+        let cases = [
+            ("flip 5", BitEdit::Flip(5, 5)),
+            ("set-bit 3", BitEdit::Set(3, 3)),
+            ("clear-bits 12..15", BitEdit::Clear(12, 15)),
+            ("flip-bits 0..7", BitEdit::Flip(0, 7)),
+            ("shl", BitEdit::Shl(1)),
+            ("shr 10", BitEdit::Shr(10)),
+        ];
+        for (line, edit) in cases {
+            assert_eq!(parse_line(line, &config).unwrap(), Command::Bits(edit));
+            // an edit is shown as a directive that makes it again
+            assert_eq!(parse_line(edit.to_string(), &config).unwrap(), Command::Bits(edit));
+        }
+    }
+}
+
+#[test]
+fn test_layout_directive_parse() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("decode cr0 80050033", &config).unwrap(),
+        Command::Decode(DecodeDirective { layout: "cr0".to_string(), expr: Expr::Num(0x80050033, Radix::Hex) })
+    );
+    assert_eq!(
+        parse_line("decode efer", &config).unwrap(),
+        Command::Decode(DecodeDirective { layout: "efer".to_string(), expr: Expr::Ans })
+    );
+    assert_eq!(
+        parse_line("encode cr0 PE=1 PG = 0d1", &config).unwrap(),
+        Command::Encode(EncodeDirective {
+            layout: "cr0".to_string(),
+            fields: vec![
+                ("PE".to_string(), Expr::Num(1, Radix::Hex)),
+                ("PG".to_string(), Expr::Num(1, Radix::DecWithPrefix)),
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_full_width_literals() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let cases = [
+        ("ffff_ffff_ffff_ffff + 0", -1),
+        ("0x8000000012345067 & 0xfff", 0x67),
+        ("0b1000000000000000000000000000000000000000000000000000000000000000 + 0", i64::MIN),
+        ("0o1777777777777777777777 + 0", -1),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    assert!(parse_line("1_0000_0000_0000_0000 + 0", &config).is_err());
+
+    // a lone number converts to the bits it was written as
+    for (conv_str, output) in [("0xffff_ffff_ffff_ffff", "0d18446744073709551615"), ("-0x1", "-0d1")] {
+        match parse_line(conv_str, &config).unwrap() {
+            Command::Convert(conversion) => {
+                assert_eq!(conversion.fmt(conversion.value(0).unwrap(), &OutputFormat::default()), output)
+            }
+            _ => panic!("Should have parsed to a conversion"),
+        }
+    }
+}
+
+#[test]
+fn test_lone_literal_views() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let views: Views = "dec,signed".parse().unwrap();
+    let of = OutputFormat::default();
+    for line in ["ff", "-1", "0d41"] {
+        match parse_line(line, &config).unwrap() {
+            Command::Convert(conversion) => {
+                let val = conversion.value(0).unwrap();
+                assert_eq!(conversion.fmt_in(val, &of, &views), views.table(val, &of));
+                assert_eq!(conversion.fmt_in(val, &of, &Views::default()), conversion.fmt(val, &of));
+            }
+            _ => panic!("Should have parsed to a conversion"),
+        }
+    }
+    // a conversion written with `to` keeps to its radix
+    match parse_line("ff to dec", &config).unwrap() {
+        Command::Convert(conversion) => assert_eq!(conversion.fmt_in(255, &of, &views), "0d255"),
+        _ => panic!("Should have parsed to a conversion"),
+    }
+}
+
+#[test]
+fn test_va_directive_parse() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("va ffff800012345678", &config).unwrap(),
+        Command::Va(VaDirective { scheme: None, expr: Expr::Num(0xffff800012345678u64 as i64, Radix::Hex) })
+    );
+    assert_eq!(
+        parse_line("va sv39", &config).unwrap(),
+        Command::Va(VaDirective { scheme: Some(PagingScheme::Sv39), expr: Expr::Ans })
+    );
+    assert_eq!(
+        parse_line("mkva x86_5level pml5=1 offset=0x10", &config).unwrap(),
+        Command::MkVa(MkVaDirective {
+            scheme: Some(PagingScheme::X86FiveLevel),
+            fields: vec![
+                ("pml5".to_string(), Expr::Num(1, Radix::Hex)),
+                ("offset".to_string(), Expr::Num(0x10, Radix::HexWithPrefix)),
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_module_directive_parse() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("module ntdll = 7ffb_1230_0000, 0x180000000, 1f0000", &config).unwrap(),
+        Command::Module(ModuleDirective {
+            name: "ntdll".to_string(),
+            base: Expr::Num(0x7ffb_1230_0000, Radix::Hex),
+            image_base: Some(Expr::Num(0x1_8000_0000, Radix::HexWithPrefix)),
+            size: Some(Expr::Num(0x1f_0000, Radix::Hex)),
+        })
+    );
+    assert_eq!(
+        parse_line("rebase(140001000, dbghelp)", &config).unwrap(),
+        Command::Expr(Expr::Call(
+            "rebase".to_string(),
+            vec![Expr::Num(0x1_4000_1000, Radix::Hex), Expr::Name("dbghelp".to_string())]
+        ))
+    );
+    // module names that are also hex numbers
+    assert_eq!(
+        parse_line("rva(ans, cafe)", &config).unwrap(),
+        Command::Expr(Expr::Call("rva".to_string(), vec![Expr::Ans, Expr::Name("cafe".to_string())]))
+    );
+    assert_eq!(parse_line("whereis", &config).unwrap(), Command::WhereIs(Expr::Ans));
+
+    // without a size, a module still locates the addresses above its base
+    let mut modules = crate::modules::Modules::default();
+    match parse_line("module ntdll = 0x7ffb_1230_0000, 0x180000000", &config).unwrap() {
+        Command::Module(module) => {
+            let (base, image_base, size) = module.values(&EvalContext::new(0)).unwrap();
+            assert_eq!(size, None);
+            modules.define(module.name(), base, image_base, size);
+        }
+        _ => panic!("Should have parsed to a module directive"),
+    }
+    assert_eq!(modules.locate(0x7ffb_1230_1234).map(|location| location.offset), Some(0x1234));
+    let ctx = EvalContext::new(0).with_modules(&modules);
+    match parse_line("rva(rebase(180001234, ntdll), ntdll)", &config).unwrap() {
+        Command::Expr(expr) => assert_eq!(eval_expr_with(&expr, &ctx).unwrap(), 0x1234),
+        _ => panic!("Should have parsed to an expr"),
+    }
+}
+
+#[test]
+fn test_cyclic_parse() {
+    let mut config: Config = Config::new();
+    config.set_mode("dec".to_string());
+    assert_eq!(parse_line("cyclic(100)", &config).unwrap(), Command::Cyclic(Expr::new_num(100)));
+
+    let ctx = EvalContext::new(0).with_bytes(32, Endian::Big);
+    match parse_line("cyclic_find(0x6c616161)", &config).unwrap() {
+        Command::Expr(expr) => assert_eq!(eval_expr_with(&expr, &ctx).unwrap(), 44),
+        _ => panic!("Should have parsed to an expr"),
+    }
+
+    let cyclic = Cyclic::new("ab", 3).unwrap();
+    let ctx = ctx.with_cyclic(&cyclic);
+    match parse_line("cyclic_find(0x61626262)", &config).unwrap() {
+        Command::Expr(expr) => assert_eq!(eval_expr_with(&expr, &ctx).unwrap(), 4),
+        _ => panic!("Should have parsed to an expr"),
+    }
+}
+
+#[test]
+fn test_string_args() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line(r#"crc32("a\"\x00\\")"#, &config).unwrap(),
+        Command::Expr(Expr::Call("crc32".to_string(), vec![Expr::Str(b"a\"\0\\".to_vec())]))
+    );
+    assert!(parse_line(r#"crc32("\q")"#, &config).is_err());
+
+    let cases = [
+        (r#"ror13("LoadLibraryA")"#, 0xec0e4e8e),
+        (r#"crc32("123456789") ^ 1"#, 0xcbf43927),
+        (r#"murmur3_32("Hello, world!", 0d1234)"#, 0xfaf6cdb3),
+        ("adler32(0)", 0x80001),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+}
+
+#[test]
+fn test_hashlookup_parse() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("hashlookup ror13 ec0e4e8e", &config).unwrap(),
+        Command::HashLookup(HashLookupDirective { algo: HashAlgo::Ror13, expr: Expr::Num(0xec0e4e8e, Radix::Hex) })
+    );
+    assert!(parse_line("hashlookup md5 1", &config).is_err());
+}
+
+#[test]
+fn test_encoding_functions() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let ctx = EvalContext::new(0).with_bytes(32, Endian::Little);
+    let cases = [
+        (r#"b64enc("hello")"#, Value::Text("aGVsbG8=".to_string())),
+        (r#"b64dec("aGVsbG8=")"#, Value::Bytes(b"hello".to_vec())),
+        ("hexenc(41424344)", Value::Text("44434241".to_string())),
+        (r#"hexdec("41 42")"#, Value::Bytes(b"AB".to_vec())),
+        (r#"cstr("A\n")"#, Value::Text("\\x41\\x0a".to_string())),
+        (r#"urldec(urlenc("a b"))"#, Value::Bytes(b"a b".to_vec())),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_value(&expr, &ctx).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    // decoded bytes read back as the number that was encoded
+    match parse_line("b64dec(b64enc(cafe)) + 1", &config).unwrap() {
+        Command::Expr(expr) => assert_eq!(eval_expr_with(&expr, &ctx).unwrap(), 0xcaff),
+        _ => panic!("Should have parsed to an expr"),
+    }
+    match parse_line(r#"hexdec("000102030405060708")"#, &config).unwrap() {
+        Command::Expr(expr) => assert!(eval_expr_with(&expr, &ctx).is_err()),
+        _ => panic!("Should have parsed to an expr"),
+    }
+    assert_eq!(
+        parse_line("cafe to hexstr", &config).unwrap(),
+        Command::Convert(ConvDirective { expr: Expr::Num(0xcafe, Radix::Hex), radix: FormatRadix::HexString, explicit: true, unsigned: false })
+    );
+}
+
+#[test]
+fn test_xor_bytes() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let ctx = EvalContext::new(0).with_bytes(32, Endian::Little);
+    let cases = [
+        (r#"xor("hello", 20)"#, Value::Bytes(b"HELLO".to_vec())),
+        (r#"xor("abcd", "\x01\x02")"#, Value::Bytes(b"``bf".to_vec())),
+        (r#"xor("abc", 0201)"#, Value::Bytes(b"``b".to_vec())),
+        (r#""hello" ^ 20"#, Value::Bytes(b"HELLO".to_vec())),
+        (r#"20 ^ "hi""#, Value::Bytes(b"HI".to_vec())),
+        ("ff ^ 0f", Value::Int(0xf0)),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_value(&expr, &ctx).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    assert_eq!(
+        parse_line(r#"xorbrute "\x12""#, &config).unwrap(),
+        Command::XorBrute(Expr::Str(b"\x12".to_vec()))
+    );
+}
+
+#[test]
+fn test_leb128_functions() {
+    let mut config: Config = Config::new();
+    config.set_mode("dec".to_string());
+    let ctx = EvalContext::new(0).with_bytes(64, Endian::Little);
+    let cases = [
+        ("uleb128(624485)", Value::Bytes(vec![0xe5, 0x8e, 0x26])),
+        ("sleb128(-123456)", Value::Bytes(vec![0xc0, 0xbb, 0x78])),
+        ("varint(300)", Value::Bytes(vec![0xac, 0x02])),
+        ("uleb128dec(uleb128(624485))", Value::Int(624485)),
+        ("sleb128dec(sleb128(-123456))", Value::Int(-123456)),
+        (r#"varintdec(hexdec("ac02"))"#, Value::Int(300)),
+        ("varintdec(0x02ac)", Value::Int(300)),
+        ("zigzag(-3)", Value::Int(5)),
+        ("unzigzag(5)", Value::Int(-3)),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_value(&expr, &ctx).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    match parse_line(r#"uleb128dec("\x80")"#, &config).unwrap() {
+        Command::Expr(expr) => {
+            assert_eq!(eval_value(&expr, &ctx), Err(CorkError::Truncated("uleb128".to_string())))
+        }
+        _ => panic!("Should have parsed to an expr"),
+    }
+}
+
+#[test]
+fn test_unicode_functions() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("utf16le('é')", &config).unwrap(),
+        Command::Expr(Expr::Call("utf16le".to_string(), vec![Expr::Str("é".as_bytes().to_vec())]))
+    );
+    let ctx = EvalContext::new(0).with_bytes(64, Endian::Little);
+    let cases = [
+        ("utf8(1F600)", Value::Bytes(vec![0xf0, 0x9f, 0x98, 0x80])),
+        ("utf16le('é')", Value::Bytes(vec![0xe9, 0x00])),
+        ("utf16le(1F600)", Value::Bytes(vec![0x3d, 0xd8, 0x00, 0xde])),
+        (r#"utf8dec("A\xc3\xa9")"#, Value::Text("U+0041  'A'  LATIN CAPITAL LETTER A\nU+00E9  'é'  LATIN SMALL LETTER E WITH ACUTE".to_string())),
+        ("utf16ledec(utf16le(a))", Value::Text("U+000A    LINE FEED".to_string())),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_value(&expr, &ctx).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    match parse_line("utf8(d800)", &config).unwrap() {
+        Command::Expr(expr) => assert!(eval_value(&expr, &ctx).is_err()),
+        _ => panic!("Should have parsed to an expr"),
+    }
+}
+
+#[test]
+fn test_timestamps() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("0x01d9a3c2e4f5a600 to filetime", &config).unwrap(),
+        Command::Convert(ConvDirective { expr: Expr::Num(0x01d9a3c2e4f5a600, Radix::HexWithPrefix), radix: FormatRadix::FileTime, explicit: true, unsigned: false })
+    );
+    assert_eq!(
+        parse_line("ans to unix_ms", &config).unwrap(),
+        Command::Convert(ConvDirective { expr: Expr::Ans, radix: FormatRadix::UnixMs, explicit: true, unsigned: false })
+    );
+    let cases = [
+        (r#"filetime("2023-06-20T22:02:24.4427264Z")"#, 0x01d9a3c2e4f5a600),
+        (r#"unix("2023-06-12T10:31:22Z")"#, 0x6486f3fa),
+        (r#"dostime("2023-06-12 10:30:22")"#, 0x56cc53cb),
+        ("filetime(0)", 116_444_736_000_000_000),
+        ("mactime(0)", -978_307_200),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+}
+
+#[test]
+fn test_ip_addresses() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("10.0.0.1 to ipv4", &config).unwrap(),
+        Command::Convert(ConvDirective { expr: Expr::Ipv4(0x0a00_0001), radix: FormatRadix::Ipv4, explicit: true, unsigned: false })
+    );
+    assert_eq!(
+        parse_line("fe80::1", &config).unwrap(),
+        Command::Expr(Expr::Ipv6(0xfe80 << 112 | 1))
+    );
+    assert!(parse_line("10.0.0.256", &config).is_err());
+    for (conv_str, expected) in [
+        ("fe80::1 to ipv6", Value::Ipv6(0xfe80 << 112 | 1)),
+        ("network(fe80::1234:5678, 64) to ipv6", Value::Ipv6(0xfe80 << 112)),
+        ("0x1 to ipv6", Value::Int(1)),
+    ] {
+        match parse_line(conv_str, &config).unwrap() {
+            Command::Convert(conversion) => {
+                assert_eq!(conversion.value_in(&EvalContext::new(0)).unwrap(), expected, "{}", conv_str)
+            }
+            _ => panic!("Should have parsed to a conversion"),
+        }
+    }
+    // prefix lengths are decimal in hex mode too
+    for (expr_str, expected) in [("netmask(24)", 0xffff_ff00), ("hosts(0d30)", 2), ("network(c0a8014d, 18)", 0xc0a8_0000)] {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    match parse_line("netmask(1f)", &config).unwrap() {
+        Command::Expr(expr) => assert!(eval_expr(&expr, 0).is_err()),
+        _ => panic!("Should have parsed to an expr"),
+    }
+
+    config.set_mode("dec".to_string());
+    let cases = [
+        ("192.168.1.10 + 5", Value::Int(0xc0a8_010f)),
+        ("netmask(24)", Value::Int(0xffff_ff00)),
+        ("network(192.168.1.77, 24)", Value::Int(0xc0a8_0100)),
+        ("broadcast(192.168.1.77, 26)", Value::Int(0xc0a8_017f)),
+        ("hosts(30)", Value::Int(2)),
+        ("network(2001:db8:aa:bb::1, 48)", Value::Ipv6(0x2001_0db8_00aa << 80)),
+        ("broadcast(fe80::1, 64)", Value::Ipv6(0xfe80 << 112 | u64::MAX as u128)),
+        ("::ffff:10.0.0.1", Value::Ipv6(0xffff_0a00_0001)),
+        ("fe80::ff + 1", Value::Ipv6(0xfe80 << 112 | 0x100)),
+        ("fe80::1 - 2", Value::Ipv6(0xfe7f << 112 | u128::MAX >> 16)),
+        ("fe80::1234 & ffff::", Value::Ipv6(0xfe80 << 112)),
+        ("fe80::1 | ::ff00", Value::Ipv6(0xfe80 << 112 | 0xff01)),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_value(&expr, &EvalContext::new(0)).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    for expr_str in ["netmask(33)", "network(0x100000000, 8)", "2001:db8::1 * 2"] {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert!(eval_expr(&expr, 0).is_err(), "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+}
+
+#[test]
+fn test_flags() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(
+        parse_line("flags prot PROT_READ|PROT_EXEC", &config).unwrap(),
+        Command::Flags(FlagsDirective {
+            table: FlagTable::Prot,
+            expr: Expr::BinOp(BinOpExpr {
+                left: Box::new(Expr::Flag("PROT_READ".to_string())),
+                right: Box::new(Expr::Flag("PROT_EXEC".to_string())),
+                op: Op::Or,
+            }),
+        })
+    );
+    assert_eq!(
+        parse_line("flags mode", &config).unwrap(),
+        Command::Flags(FlagsDirective { table: FlagTable::Mode, expr: Expr::Ans })
+    );
+    // names made of hex digits only stay numbers
+    assert_eq!(
+        parse_line("DEAD", &config).unwrap(),
+        Command::Convert(ConvDirective { expr: Expr::Num(0xdead, Radix::Hex), radix: FormatRadix::Decimal, explicit: false, unsigned: true })
+    );
+    assert!(parse_line("flags perms 0", &config).is_err());
+
+    let cases = [("S_IFDIR|S_IRWXU", 0o40700), ("O_RDWR|O_CLOEXEC", 0o2000002), ("MAP_ANON + 2", 0x22)];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+}
+
+#[test]
+fn test_whatis_parse() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    assert_eq!(parse_line("whatis", &config).unwrap(), Command::WhatIs(Expr::Ans));
+    assert_eq!(
+        parse_line("whatis c0000005", &config).unwrap(),
+        Command::WhatIs(Expr::Num(0xc000_0005, Radix::Hex))
+    );
+    assert_eq!(
+        parse_line("whatis -0d13", &config).unwrap(),
+        Command::WhatIs(Expr::Num(-13, Radix::DecWithPrefix))
+    );
+}
+
+#[test]
+fn test_instruction_functions() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let cases = [
+        ("rel32(401000, 401100)", 0xfb),
+        ("rel32(401100, 401000, 6)", -0x106),
+        ("adrp(40001234, 40123456)", 0x122),
+        ("b(1000, 2000)", 0x1400_0400),
+        ("bl(2000, 1000)", 0x97ff_fc00),
+        ("bdec(bl(2000, 1000), 2000)", 0x1000),
+        ("armimm(ff000000)", 0x4ff),
+        ("armimmdec(4ff)", 0xff00_0000),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    for expr_str in ["rel32(1000, 100001000)", "b(0, 8000000)", "armimm(101)", "bdec(d503201f, 0)"] {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert!(eval_expr(&expr, 0).is_err(), "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+}
+
+#[test]
+fn test_bit_masks() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let cases = [
+        ("mask(f, 8)", 0xff00),
+        ("genmask_ull(3f, 0)", -1),
+        ("bits(0, 3, 7)", 0x89),
+        ("bit(4)", 0x10),
+        ("bit_ull(3f)", i64::MIN),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    for expr_str in ["mask(8, f)", "bits(40)", "bit(1, 2)"] {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert!(eval_expr(&expr, 0).is_err(), "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    match parse_line("8000ff00 to ranges", &config).unwrap() {
+        Command::Convert(conversion) => assert_eq!(conversion.radix(), FormatRadix::Ranges),
+        _ => panic!("Should have parsed to a convert"),
+    }
+}
+
+#[test]
+fn test_integer_encodings() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let cases = [
+        ("bcd(0d1234)", 0x1234),
+        ("unbcd(1234)", 1234),
+        ("bcd_unpacked(0d42)", 0x0402),
+        ("unbcd_unpacked(0402)", 42),
+        ("gray(5)", 7),
+        ("ungray(7)", 5),
+        ("excess(-3, 7f)", 0x7c),
+        ("unexcess(7c, 7f)", -3),
+        ("ones(-1)", -2),
+        ("unones(ones(-5))", -5),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    for expr_str in ["unbcd(1a)", "bcd(-1)", "unbcd_unpacked(0a)"] {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) => assert!(eval_expr(&expr, 0).is_err(), "{}", expr_str),
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    match parse_line("ans to bcd_unpacked", &config).unwrap() {
+        Command::Convert(conversion) => assert_eq!(conversion.radix(), FormatRadix::UnpackedBcd),
+        _ => panic!("Should have parsed to a convert"),
+    }
+}
+
+#[test]
+fn test_base_literals() {
+    let mut config: Config = Config::new();
+    config.set_mode("hex".to_string());
+    let cases = [
+        ("0r36:zz", 1295),
+        ("0r3:10201 + 1", 101),
+        ("-0r36:z", -35),
+        ("0r2:1111_0000", 0xf0),
+        ("0rbase58:21", 58),
+        ("0rcrockford32:16j", 1234),
+    ];
+    for (expr_str, expected) in cases {
+        match parse_line(expr_str, &config).unwrap() {
+            Command::Expr(expr) | Command::Convert(ConvDirective { expr, .. }) => {
+                assert_eq!(eval_expr(&expr, 0).unwrap(), expected, "{}", expr_str)
+            }
+            _ => panic!("Should have parsed to an expr"),
+        }
+    }
+    for expr_str in ["0r37:1", "0r8:9", "0rbase58:0", "0rbase64:1"] {
+        assert!(parse_line(expr_str, &config).is_err(), "{}", expr_str);
+    }
+    let radices = [
+        ("ff to base36", FormatRadix::Base(36)),
+        ("ff to base16", FormatRadix::Hex),
+        ("ff to base58", FormatRadix::Alphabet(Alphabet::Base58)),
+        ("ff to crockford32", FormatRadix::Alphabet(Alphabet::Crockford32)),
+    ];
+    for (conv_str, radix) in radices {
+        match parse_line(conv_str, &config).unwrap() {
+            Command::Convert(conversion) => assert_eq!(conversion.radix(), radix, "{}", conv_str),
+            _ => panic!("Should have parsed to a convert"),
+        }
+    }
+    assert!(parse_line("ff to base37", &config).is_err());
+}
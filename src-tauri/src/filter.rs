@@ -0,0 +1,107 @@
+use crate::expression;
+
+/// A Piece is a part of a line that the filter rewrites: a number with a
+/// radix prefix, or a `$(...)` expression. Numbers without a prefix are
+/// left alone, in text they are as likely to be line numbers or counts.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Piece<'a> {
+    /// The byte range of the piece in the line, `$(` and `)` included.
+    pub span: (usize, usize),
+    /// What is evaluated, the number or the expression inside `$(...)`.
+    pub expr: &'a str,
+}
+
+/// pieces finds what the filter rewrites in `line`, from left to right.
+pub fn pieces(line: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = line[pos..].chars().next() {
+        let rest = &line[pos..];
+        let piece = if rest.starts_with("$(") {
+            closing_paren(&rest[1..]).map(|len| Piece { span: (pos, pos + len + 1), expr: &rest[2..len] })
+        } else if !line[..pos].ends_with(is_word_char) {
+            expression::prefixed_literal(rest)
+                .filter(|&len| !rest[len..].starts_with(is_word_char))
+                .map(|len| Piece { span: (pos, pos + len), expr: &rest[..len] })
+        } else {
+            None
+        };
+        match piece {
+            Some(piece) => {
+                pos = piece.span.1;
+                pieces.push(piece);
+            }
+            None => pos += c.len_utf8(),
+        }
+    }
+    pieces
+}
+
+/// rewrite replaces every piece of `line` with what `eval` gives for it.
+/// The pieces `eval` fails on stay as they are, their errors are given
+/// along with the line.
+pub fn rewrite<E>(line: &str, mut eval: impl FnMut(&str) -> Result<String, E>) -> (String, Vec<E>) {
+    let mut rewritten = String::with_capacity(line.len());
+    let mut errors = Vec::new();
+    let mut end = 0;
+    for piece in pieces(line) {
+        rewritten.push_str(&line[end..piece.span.0]);
+        match eval(piece.expr) {
+            Ok(value) => rewritten.push_str(&value),
+            Err(err) => {
+                rewritten.push_str(&line[piece.span.0..piece.span.1]);
+                errors.push(err);
+            }
+        }
+        end = piece.span.1;
+    }
+    rewritten.push_str(&line[end..]);
+    (rewritten, errors)
+}
+
+/// closing_paren gives the length of `text` up to and including the paren
+/// that closes the one it starts with.
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => continue,
+        }
+        if depth == 0 {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pieces() {
+        let exprs = |line| pieces(line).into_iter().map(|piece| piece.expr).collect::<Vec<_>>();
+        assert_eq!(exprs("mov rax, 0x401000 ; 0b101+0o7"), ["0x401000", "0b101", "0o7"]);
+        assert_eq!(exprs("size $((1 << 4) * 2) at 0r36:zz"), ["(1 << 4) * 2", "0r36:zz"]);
+        // numbers inside words, without a prefix, or not closed are left alone
+        assert_eq!(exprs("x0x10 0x10h 1234 0xg $(1 + 2"), Vec::<&str>::new());
+        assert_eq!(pieces("é $(1)")[0].span, (3, 7));
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let eval = |expr: &str| match expr {
+            "0x10" => Ok("16".to_string()),
+            _ => Err(expr.to_string()),
+        };
+        let (line, errors) = rewrite("a 0x10, $(oops) b", eval);
+        assert_eq!(line, "a 16, $(oops) b");
+        assert_eq!(errors, ["oops"]);
+    }
+}
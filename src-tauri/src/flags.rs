@@ -0,0 +1,302 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::CorkError;
+use strum::{EnumIter, IntoEnumIterator};
+
+/// A FlagTable is a set of named flags that make up a value, such as the
+/// protection of a mapping. The Unix tables have the Linux values, the
+/// Windows ones those of the SDK headers.
+#[derive(EnumIter, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagTable {
+    /// `st_mode` of stat(2): file type and permission bits
+    Mode,
+    /// `O_*` flags of open(2)
+    Open,
+    /// `PROT_*` protection of mmap(2) and mprotect(2)
+    Prot,
+    /// `MAP_*` flags of mmap(2)
+    Map,
+    /// Windows `PAGE_*` memory protection
+    Page,
+    /// Windows `ACCESS_MASK` rights, with the file specific ones
+    Access,
+}
+
+/// A Flag is set in a value when the bits under `mask` equal `value`. Most
+/// flags are a single bit and their own mask; the others pick one value
+/// out of a field, like the access mode of open(2).
+struct Flag {
+    name: &'static str,
+    value: u64,
+    mask: u64,
+}
+
+const fn bit(name: &'static str, value: u64) -> Flag {
+    Flag { name, value, mask: value }
+}
+
+const fn field(name: &'static str, value: u64, mask: u64) -> Flag {
+    Flag { name, value, mask }
+}
+
+// Flags are matched in order and a flag is skipped once another one took
+// any of its bits, so wider names come before the bits they cover.
+
+const S_IFMT: u64 = 0o170000;
+
+static MODE: &[Flag] = &[
+    field("S_IFSOCK", 0o140000, S_IFMT),
+    field("S_IFLNK", 0o120000, S_IFMT),
+    field("S_IFREG", 0o100000, S_IFMT),
+    field("S_IFBLK", 0o060000, S_IFMT),
+    field("S_IFDIR", 0o040000, S_IFMT),
+    field("S_IFCHR", 0o020000, S_IFMT),
+    field("S_IFIFO", 0o010000, S_IFMT),
+    bit("S_ISUID", 0o4000),
+    bit("S_ISGID", 0o2000),
+    bit("S_ISVTX", 0o1000),
+    bit("S_IRWXU", 0o700),
+    bit("S_IRUSR", 0o400),
+    bit("S_IWUSR", 0o200),
+    bit("S_IXUSR", 0o100),
+    bit("S_IRWXG", 0o070),
+    bit("S_IRGRP", 0o040),
+    bit("S_IWGRP", 0o020),
+    bit("S_IXGRP", 0o010),
+    bit("S_IRWXO", 0o007),
+    bit("S_IROTH", 0o004),
+    bit("S_IWOTH", 0o002),
+    bit("S_IXOTH", 0o001),
+];
+
+static OPEN: &[Flag] = &[
+    field("O_RDONLY", 0, 0o3),
+    field("O_WRONLY", 0o1, 0o3),
+    field("O_RDWR", 0o2, 0o3),
+    bit("O_CREAT", 0o100),
+    bit("O_EXCL", 0o200),
+    bit("O_NOCTTY", 0o400),
+    bit("O_TRUNC", 0o1000),
+    bit("O_APPEND", 0o2000),
+    bit("O_NONBLOCK", 0o4000),
+    bit("O_SYNC", 0o4010000),
+    bit("O_DSYNC", 0o10000),
+    bit("O_ASYNC", 0o20000),
+    bit("O_DIRECT", 0o40000),
+    bit("O_LARGEFILE", 0o100000),
+    bit("O_TMPFILE", 0o20200000),
+    bit("O_DIRECTORY", 0o200000),
+    bit("O_NOFOLLOW", 0o400000),
+    bit("O_NOATIME", 0o1000000),
+    bit("O_CLOEXEC", 0o2000000),
+    bit("O_PATH", 0o10000000),
+];
+
+static PROT: &[Flag] = &[
+    field("PROT_NONE", 0, 0x7),
+    bit("PROT_READ", 0x1),
+    bit("PROT_WRITE", 0x2),
+    bit("PROT_EXEC", 0x4),
+    bit("PROT_SEM", 0x8),
+    bit("PROT_GROWSDOWN", 0x0100_0000),
+    bit("PROT_GROWSUP", 0x0200_0000),
+];
+
+static MAP: &[Flag] = &[
+    field("MAP_SHARED_VALIDATE", 0x3, 0x3),
+    field("MAP_SHARED", 0x1, 0x3),
+    field("MAP_PRIVATE", 0x2, 0x3),
+    bit("MAP_FIXED", 0x10),
+    bit("MAP_ANONYMOUS", 0x20),
+    bit("MAP_ANON", 0x20),
+    bit("MAP_32BIT", 0x40),
+    bit("MAP_GROWSDOWN", 0x100),
+    bit("MAP_DENYWRITE", 0x800),
+    bit("MAP_EXECUTABLE", 0x1000),
+    bit("MAP_LOCKED", 0x2000),
+    bit("MAP_NORESERVE", 0x4000),
+    bit("MAP_POPULATE", 0x8000),
+    bit("MAP_NONBLOCK", 0x10000),
+    bit("MAP_STACK", 0x20000),
+    bit("MAP_HUGETLB", 0x40000),
+    bit("MAP_SYNC", 0x80000),
+    bit("MAP_FIXED_NOREPLACE", 0x100000),
+    bit("MAP_UNINITIALIZED", 0x4000000),
+];
+
+static PAGE: &[Flag] = &[
+    field("PAGE_NOACCESS", 0x01, 0xff),
+    field("PAGE_READONLY", 0x02, 0xff),
+    field("PAGE_READWRITE", 0x04, 0xff),
+    field("PAGE_WRITECOPY", 0x08, 0xff),
+    field("PAGE_EXECUTE", 0x10, 0xff),
+    field("PAGE_EXECUTE_READ", 0x20, 0xff),
+    field("PAGE_EXECUTE_READWRITE", 0x40, 0xff),
+    field("PAGE_EXECUTE_WRITECOPY", 0x80, 0xff),
+    bit("PAGE_GUARD", 0x100),
+    bit("PAGE_NOCACHE", 0x200),
+    bit("PAGE_WRITECOMBINE", 0x400),
+    bit("PAGE_TARGETS_INVALID", 0x4000_0000),
+];
+
+static ACCESS: &[Flag] = &[
+    bit("GENERIC_READ", 0x8000_0000),
+    bit("GENERIC_WRITE", 0x4000_0000),
+    bit("GENERIC_EXECUTE", 0x2000_0000),
+    bit("GENERIC_ALL", 0x1000_0000),
+    bit("MAXIMUM_ALLOWED", 0x0200_0000),
+    bit("ACCESS_SYSTEM_SECURITY", 0x0100_0000),
+    bit("FILE_ALL_ACCESS", 0x001f_01ff),
+    bit("STANDARD_RIGHTS_ALL", 0x001f_0000),
+    bit("STANDARD_RIGHTS_REQUIRED", 0x000f_0000),
+    bit("SYNCHRONIZE", 0x0010_0000),
+    bit("WRITE_OWNER", 0x0008_0000),
+    bit("WRITE_DAC", 0x0004_0000),
+    bit("READ_CONTROL", 0x0002_0000),
+    bit("DELETE", 0x0001_0000),
+    bit("FILE_READ_DATA", 0x0001),
+    bit("FILE_WRITE_DATA", 0x0002),
+    bit("FILE_APPEND_DATA", 0x0004),
+    bit("FILE_READ_EA", 0x0008),
+    bit("FILE_WRITE_EA", 0x0010),
+    bit("FILE_EXECUTE", 0x0020),
+    bit("FILE_DELETE_CHILD", 0x0040),
+    bit("FILE_READ_ATTRIBUTES", 0x0080),
+    bit("FILE_WRITE_ATTRIBUTES", 0x0100),
+];
+
+impl FlagTable {
+    /// name is the table name the `flags` directive takes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlagTable::Mode => "mode",
+            FlagTable::Open => "open",
+            FlagTable::Prot => "prot",
+            FlagTable::Map => "map",
+            FlagTable::Page => "page",
+            FlagTable::Access => "access",
+        }
+    }
+
+    fn flags(&self) -> &'static [Flag] {
+        match self {
+            FlagTable::Mode => MODE,
+            FlagTable::Open => OPEN,
+            FlagTable::Prot => PROT,
+            FlagTable::Map => MAP,
+            FlagTable::Page => PAGE,
+            FlagTable::Access => ACCESS,
+        }
+    }
+
+    /// names lists the flags set in `value`, joined with `|`. Bits no flag
+    /// covers are added as a hex number.
+    pub fn names(&self, value: u64) -> String {
+        let mut names = Vec::new();
+        let mut taken = 0;
+        for flag in self.flags() {
+            if value & flag.mask == flag.value && flag.mask & taken == 0 {
+                names.push(flag.name.to_string());
+                taken |= flag.mask;
+            }
+        }
+        let rest = value & !taken;
+        if rest != 0 || names.is_empty() {
+            names.push(format!("{:#x}", rest));
+        }
+        names.join("|")
+    }
+
+    /// render shows `value` the way tools do: `ls -l` permissions for a
+    /// mode, the flag names otherwise.
+    pub fn render(&self, value: u64) -> String {
+        match self {
+            FlagTable::Mode => format!("{}  {}", fmt_mode(value), self.names(value)),
+            _ => self.names(value),
+        }
+    }
+}
+
+impl fmt::Display for FlagTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for FlagTable {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FlagTable::iter()
+            .find(|table| table.name() == s)
+            .ok_or_else(|| CorkError::Eval(format!("{} is not a known flag table", s)))
+    }
+}
+
+/// lookup gives the value of a flag name from any of the tables.
+pub fn lookup(name: &str) -> Result<i64, CorkError> {
+    FlagTable::iter()
+        .flat_map(|table| table.flags())
+        .find(|flag| flag.name == name)
+        .map(|flag| flag.value as i64)
+        .ok_or_else(|| CorkError::Eval(format!("{} is not a known flag", name)))
+}
+
+/// fmt_mode writes permission bits like `ls -l` does, `rwsr-xr-x`, led by
+/// the file type when the value has one.
+pub fn fmt_mode(value: u64) -> String {
+    let file_type = match value & S_IFMT {
+        0 => "",
+        0o140000 => "s",
+        0o120000 => "l",
+        0o100000 => "-",
+        0o060000 => "b",
+        0o040000 => "d",
+        0o020000 => "c",
+        0o010000 => "p",
+        _ => "?",
+    };
+    let mut perms = String::from(file_type);
+    // (who, set-id or sticky bit, its letter)
+    for (shift, special, letter) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = value >> shift;
+        perms.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        perms.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        perms.push(match (bits & 0o1 != 0, value & special != 0) {
+            (true, true) => letter,
+            (false, true) => letter.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    perms
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flag_names() {
+        assert_eq!(FlagTable::Prot.names(5), "PROT_READ|PROT_EXEC");
+        assert_eq!(FlagTable::Prot.names(0), "PROT_NONE");
+        assert_eq!(FlagTable::Open.names(0o2101), "O_WRONLY|O_CREAT|O_APPEND");
+        assert_eq!(FlagTable::Open.names(0o4010002), "O_RDWR|O_SYNC");
+        assert_eq!(FlagTable::Map.names(0x22), "MAP_PRIVATE|MAP_ANONYMOUS");
+        assert_eq!(FlagTable::Page.names(0x140), "PAGE_EXECUTE_READWRITE|PAGE_GUARD");
+        assert_eq!(FlagTable::Page.names(0x3), "0x3");
+        assert_eq!(FlagTable::Access.names(0x0012_0089), "SYNCHRONIZE|READ_CONTROL|FILE_READ_DATA|FILE_READ_EA|FILE_READ_ATTRIBUTES");
+        assert_eq!(FlagTable::Mode.names(0o4755), "S_ISUID|S_IRWXU|S_IRGRP|S_IXGRP|S_IROTH|S_IXOTH");
+        assert_eq!(lookup("PROT_WRITE").unwrap(), 2);
+        assert_eq!(lookup("MAP_ANON").unwrap(), 0x20);
+        assert!(lookup("PROT_FOO").is_err());
+    }
+
+    #[test]
+    fn test_fmt_mode() {
+        assert_eq!(fmt_mode(0o4755), "rwsr-xr-x");
+        assert_eq!(fmt_mode(0o100644), "-rw-r--r--");
+        assert_eq!(fmt_mode(0o041777), "drwxrwxrwt");
+        assert_eq!(fmt_mode(0o2640), "rw-r-S---");
+    }
+}
@@ -3,38 +3,161 @@ use std::{
     fmt::{self, Display, Formatter},
 };
 
+use crate::{
+    bits,
+    encoding::{self, Alphabet},
+    error::CorkError,
+    net,
+    timestamp::Epoch,
+    unicode,
+};
 use colored::*;
-use serde::Deserialize;
-use strum::EnumIter;
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
 
-#[derive(EnumIter, Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[derive(EnumIter, Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum FormatRadix {
     Decimal,
     Hex,
     Octal,
     Binary,
+    Base64,
+    HexString,
+    CString,
+    Varint,
+    Char,
+    Unix,
+    UnixMs,
+    UnixNs,
+    FileTime,
+    MacTime,
+    DosTime,
+    Ipv4,
+    Ipv6,
+    Ranges,
+    Gray,
+    Bcd,
+    UnpackedBcd,
+    Excess,
+    OnesComplement,
+    /// Digits in any radix from 2 to 36.
+    #[strum(disabled)]
+    Base(u32),
+    #[strum(disabled)]
+    Alphabet(Alphabet),
 }
 
 impl FormatRadix {
-    fn fmt_uint_to_chars(&self, num: u64) -> Vec<char> {
-        let mut rev_chars = match self {
-            FormatRadix::Decimal => uint_to_chars_radix(num, 10),
-            FormatRadix::Hex => uint_to_chars_radix(num, 16),
-            FormatRadix::Octal => uint_to_chars_radix(num, 8),
-            FormatRadix::Binary => uint_to_chars_radix(num, 2),
+    /// radix gives the base a numeric radix writes its digits in.
+    pub fn radix(&self) -> Option<u32> {
+        match self {
+            FormatRadix::Decimal => Some(10),
+            FormatRadix::Hex => Some(16),
+            FormatRadix::Octal => Some(8),
+            FormatRadix::Binary => Some(2),
+            FormatRadix::Base(radix) => Some(*radix),
+            _ => None,
+        }
+    }
+
+    /// epoch gives the timestamp the radix shows values as, if it is one.
+    pub fn epoch(&self) -> Option<Epoch> {
+        match self {
+            FormatRadix::Unix => Some(Epoch::Unix),
+            FormatRadix::UnixMs => Some(Epoch::UnixMs),
+            FormatRadix::UnixNs => Some(Epoch::UnixNs),
+            FormatRadix::FileTime => Some(Epoch::FileTime),
+            FormatRadix::MacTime => Some(Epoch::MacTime),
+            FormatRadix::DosTime => Some(Epoch::DosTime),
+            _ => None,
+        }
+    }
+
+    /// is_numeric tells whether the radix renders the digits of a value, and
+    /// so has a sign, rather than its bytes, its set bits, the character or
+    /// address it stands for or what it decodes to.
+    pub fn is_numeric(&self) -> bool {
+        self.radix().is_some()
+    }
+
+    /// radices are the four bases `--all` prints a value in.
+    pub fn radices() -> impl Iterator<Item = FormatRadix> {
+        FormatRadix::iter().filter(FormatRadix::is_numeric)
+    }
+
+    /// name gives the name the radix is written with after `to`.
+    pub fn name(&self) -> String {
+        let name = match self {
+            FormatRadix::Decimal => "dec",
+            FormatRadix::Hex => "hex",
+            FormatRadix::Octal => "oct",
+            FormatRadix::Binary => "bin",
+            FormatRadix::Base64 => "b64",
+            FormatRadix::HexString => "hexstr",
+            FormatRadix::CString => "cstr",
+            FormatRadix::Varint => "varint",
+            FormatRadix::Char => "char",
+            FormatRadix::Unix
+            | FormatRadix::UnixMs
+            | FormatRadix::UnixNs
+            | FormatRadix::FileTime
+            | FormatRadix::MacTime
+            | FormatRadix::DosTime => self.epoch().expect("timestamps have an epoch").name(),
+            FormatRadix::Ipv4 => "ipv4",
+            FormatRadix::Ipv6 => "ipv6",
+            FormatRadix::Ranges => "ranges",
+            FormatRadix::Gray => "gray",
+            FormatRadix::Bcd => "bcd",
+            FormatRadix::UnpackedBcd => "bcd_unpacked",
+            FormatRadix::Excess => "excess",
+            FormatRadix::OnesComplement => "ones",
+            FormatRadix::Base(radix) => return format!("base{}", radix),
+            FormatRadix::Alphabet(alphabet) => return alphabet.to_string(),
         };
-        rev_chars.reverse();
-        rev_chars
+        name.to_string()
+    }
+
+    /// from_base reads a `base36` style radix name, or the name of an
+    /// alphabet. The radices with a format of their own give that format.
+    pub fn from_base(name: &str) -> Result<FormatRadix, CorkError> {
+        if let Ok(alphabet) = name.parse::<Alphabet>() {
+            return Ok(FormatRadix::Alphabet(alphabet));
+        }
+        let radix = name
+            .strip_prefix("base")
+            .and_then(|radix| radix.parse().ok())
+            .ok_or_else(|| CorkError::Eval(format!("{} is not a known radix", name)))?;
+        Ok(match encoding::check_radix(radix)? {
+            2 => FormatRadix::Binary,
+            8 => FormatRadix::Octal,
+            10 => FormatRadix::Decimal,
+            16 => FormatRadix::Hex,
+            radix => FormatRadix::Base(radix),
+        })
+    }
+}
+
+impl std::str::FromStr for FormatRadix {
+    type Err = CorkError;
+
+    /// from_str reads the radix names of `to` and `set of`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match FormatRadix::iter().find(|radix| radix.name() == s) {
+            Some(radix) => Ok(radix),
+            None => FormatRadix::from_base(s),
+        }
     }
 }
 
-impl From<FormatRadix> for u32 {
-    fn from(val: FormatRadix) -> Self {
-        match val {
-            FormatRadix::Decimal => 10,
-            FormatRadix::Hex => 16,
-            FormatRadix::Octal => 8,
-            FormatRadix::Binary => 2,
+impl From<Epoch> for FormatRadix {
+    fn from(epoch: Epoch) -> Self {
+        match epoch {
+            Epoch::Unix => FormatRadix::Unix,
+            Epoch::UnixMs => FormatRadix::UnixMs,
+            Epoch::UnixNs => FormatRadix::UnixNs,
+            Epoch::FileTime => FormatRadix::FileTime,
+            Epoch::MacTime => FormatRadix::MacTime,
+            Epoch::DosTime => FormatRadix::DosTime,
         }
     }
 }
@@ -46,6 +169,28 @@ impl Display for FormatRadix {
             FormatRadix::Hex => write!(f, "{}", "Hexadecimal".yellow()),
             FormatRadix::Octal => write!(f, "{}", "Octal".blue()),
             FormatRadix::Binary => write!(f, "{}", "Binary".magenta()),
+            FormatRadix::Base64 => write!(f, "{}", "Base64".cyan()),
+            FormatRadix::HexString => write!(f, "{}", "Hex string".cyan()),
+            FormatRadix::CString => write!(f, "{}", "C string".cyan()),
+            FormatRadix::Varint => write!(f, "{}", "Varint".cyan()),
+            FormatRadix::Char => write!(f, "{}", "Character".cyan()),
+            FormatRadix::Unix => write!(f, "{}", "Unix time".red()),
+            FormatRadix::UnixMs => write!(f, "{}", "Unix time (ms)".red()),
+            FormatRadix::UnixNs => write!(f, "{}", "Unix time (ns)".red()),
+            FormatRadix::FileTime => write!(f, "{}", "FILETIME".red()),
+            FormatRadix::MacTime => write!(f, "{}", "Mac absolute time".red()),
+            FormatRadix::DosTime => write!(f, "{}", "DOS date and time".red()),
+            FormatRadix::Ipv4 => write!(f, "{}", "IPv4 address".bright_blue()),
+            FormatRadix::Ipv6 => write!(f, "{}", "IPv6 address".bright_blue()),
+            FormatRadix::Ranges => write!(f, "{}", "Bit ranges".magenta()),
+            FormatRadix::Gray => write!(f, "{}", "Gray code".bright_green()),
+            FormatRadix::Bcd => write!(f, "{}", "Packed BCD".bright_green()),
+            FormatRadix::UnpackedBcd => write!(f, "{}", "Unpacked BCD".bright_green()),
+            FormatRadix::Excess => write!(f, "{}", "Offset binary".bright_green()),
+            FormatRadix::OnesComplement => write!(f, "{}", "Ones' complement".bright_green()),
+            FormatRadix::Base(radix) => write!(f, "{}", format!("Base {}", radix).yellow()),
+            FormatRadix::Alphabet(Alphabet::Crockford32) => write!(f, "{}", "Crockford base32".cyan()),
+            FormatRadix::Alphabet(Alphabet::Base58) => write!(f, "{}", "Base58".cyan()),
         }
     }
 }
@@ -63,17 +208,55 @@ fn uint_to_chars_radix(mut num: u64, radix: u32) -> Vec<char> {
     chars
 }
 
-fn uint_with_separators(chars: &[char], radix: FormatRadix) -> String {
-    let interval = match radix {
-        FormatRadix::Decimal | FormatRadix::Octal => 3,
-        FormatRadix::Hex | FormatRadix::Binary => 4,
-    };
+fn uint_with_separators(chars: &[char], interval: usize, separator: char) -> String {
     let chunks: Vec<_> = chars
         .rchunks(interval)
         .map(String::from_iter)
         .rev()
         .collect();
-    chunks.join("_")
+    chunks.join(&separator.to_string())
+}
+
+/// visible_width counts the characters of `text` a terminal shows, leaving
+/// out the escape sequences that color it.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// align_columns lays out rows of cells as a left-aligned table, two spaces
+/// between columns. The last column is not padded.
+pub fn align_columns<T: AsRef<str>>(rows: &[Vec<T>]) -> String {
+    let mut widths = Vec::new();
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            let len = cell.as_ref().chars().count();
+            match widths.get_mut(idx) {
+                Some(width) => *width = len.max(*width),
+                None => widths.push(len),
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell.as_ref(), width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 impl Default for FormatRadix {
@@ -82,29 +265,223 @@ impl Default for FormatRadix {
     }
 }
 
-#[derive(Default)]
+/// Prefix is how a number is marked with its radix. Besides the cork
+/// prefixes, numbers can be written as assemblers or programming languages
+/// expect them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Prefix {
+    /// `0x`, `0o`, `0b` and `0d`.
+    #[default]
+    #[serde(rename = "0x")]
+    Standard,
+    #[serde(rename = "none")]
+    Bare,
+    /// Intel assembler suffixes: `0ffh`, `17o`, `101b`.
+    #[serde(rename = "h")]
+    Suffix,
+    /// Motorola assembler prefixes: `$ff`, `@17`, `%101`.
+    #[serde(rename = "$")]
+    Dollar,
+    /// C literals, with a leading 0 for octal and `'` between digit groups.
+    #[serde(rename = "c")]
+    C,
+    #[serde(rename = "rust")]
+    Rust,
+    #[serde(rename = "python")]
+    Python,
+}
+
+impl Prefix {
+    /// separator gives the digit separator the literals of the style accept.
+    fn separator(&self) -> char {
+        match self {
+            Prefix::C => '\'',
+            _ => '_',
+        }
+    }
+
+    /// mark writes the `digits` of a number in `radix` with the prefix or
+    /// suffix of the style. Radices the style has no way to write get the
+    /// cork prefix.
+    fn mark(&self, radix: FormatRadix, digits: &str) -> String {
+        let (prefix, suffix) = match (self, radix) {
+            (Prefix::Bare, _) => ("", ""),
+            (_, FormatRadix::Base(radix)) => return format!("0r{}:{}", radix, digits),
+            (Prefix::Standard, FormatRadix::Decimal) => ("0d", ""),
+            (_, FormatRadix::Decimal) => ("", ""),
+            (Prefix::Suffix, FormatRadix::Hex) if digits.starts_with(|c: char| c.is_ascii_alphabetic()) => ("0", "h"),
+            (Prefix::Suffix, FormatRadix::Hex) => ("", "h"),
+            (Prefix::Suffix, FormatRadix::Octal) => ("", "o"),
+            (Prefix::Suffix, FormatRadix::Binary) => ("", "b"),
+            (Prefix::Dollar, FormatRadix::Hex) => ("$", ""),
+            (Prefix::Dollar, FormatRadix::Octal) => ("@", ""),
+            (Prefix::Dollar, FormatRadix::Binary) => ("%", ""),
+            (Prefix::C, FormatRadix::Octal) if digits == "0" => ("", ""),
+            (Prefix::C, FormatRadix::Octal) => ("0", ""),
+            (_, FormatRadix::Hex) => ("0x", ""),
+            (_, FormatRadix::Octal) => ("0o", ""),
+            (_, FormatRadix::Binary) => ("0b", ""),
+            _ => unreachable!("byte radices have no prefix"),
+        };
+        format!("{}{}{}", prefix, digits, suffix)
+    }
+}
+
+impl std::str::FromStr for Prefix {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0x" => Ok(Prefix::Standard),
+            "none" => Ok(Prefix::Bare),
+            "h" => Ok(Prefix::Suffix),
+            "$" => Ok(Prefix::Dollar),
+            "c" => Ok(Prefix::C),
+            "rust" => Ok(Prefix::Rust),
+            "python" => Ok(Prefix::Python),
+            _ => Err(CorkError::InvalidValueForKey {
+                value: s.to_string(),
+                key: "prefix".to_string(),
+            }),
+        }
+    }
+}
+
+/// Style is how the digits of numbers are written. Digits are grouped from
+/// the right when punctuating, in every radix alike; a group size of 0
+/// turns grouping off.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct Style {
+    /// Upper case digits from A on.
+    #[serde(default)]
+    pub uppercase: bool,
+    /// Leading zeros up to the digits of the largest value of the width.
+    #[serde(default)]
+    pub zero_pad: bool,
+    #[serde(default)]
+    pub prefix: Prefix,
+    /// The separator between digit groups, the one of the prefix style when
+    /// not given.
+    #[serde(default)]
+    pub separator: Option<char>,
+    /// The digits in a group, 3 for decimal and octal and 4 for the other
+    /// radices when not given.
+    #[serde(default)]
+    pub group_size: Option<usize>,
+}
+
+/// A View is one line of the multi-view output: a radix, or the bits of
+/// the width read as a signed or an unsigned number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Radix(FormatRadix),
+    Signed,
+    Unsigned,
+}
+
+impl Display for View {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            View::Radix(radix) => radix.fmt(f),
+            View::Signed => write!(f, "{}", "Signed".green()),
+            View::Unsigned => write!(f, "{}", "Unsigned".green()),
+        }
+    }
+}
+
+/// Views are the representations every result is shown in, written as a
+/// comma separated list like `hex,dec,bin,signed`. `off`, or no views,
+/// shows results in the output format alone.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct Views(Vec<View>);
+
+impl Views {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// table renders `num` in every view, one per line, with the labels
+    /// right-aligned. The radices take the digit style of `of`.
+    pub fn table(&self, num: i64, of: &OutputFormat) -> String {
+        let labels: Vec<_> = self.0.iter().map(View::to_string).collect();
+        let label_width = labels.iter().map(|label| visible_width(label)).max().unwrap_or(0);
+        let bits = bits::to_unsigned(num, of.width);
+        let decimal = OutputFormat {
+            radix: FormatRadix::Decimal,
+            ..*of
+        };
+        self.0
+            .iter()
+            .zip(labels)
+            .map(|(view, label)| {
+                let value = match view {
+                    View::Radix(radix) => OutputFormat { radix: *radix, ..*of }.fmt(num),
+                    View::Signed => decimal.fmt(bits::to_signed(bits, of.width)),
+                    View::Unsigned => decimal.fmt_unsigned(bits),
+                };
+                let padding = " ".repeat(label_width - visible_width(&label));
+                format!("{}{}: {}", padding, label, value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl std::str::FromStr for Views {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "off" {
+            return Ok(Views::default());
+        }
+        s.split(',')
+            .map(|name| match name.trim() {
+                "signed" => Ok(View::Signed),
+                "unsigned" => Ok(View::Unsigned),
+                name => name.parse().map(View::Radix),
+            })
+            .collect::<Result<_, _>>()
+            .map(Views)
+    }
+}
+
+impl FromIterator<View> for Views {
+    fn from_iter<I: IntoIterator<Item = View>>(views: I) -> Self {
+        Views(views.into_iter().collect())
+    }
+}
+
+impl TryFrom<String> for Views {
+    type Error = CorkError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// OutputFormat renders numbers in a radix. The byte radices lay values
+/// out in `width` bits with the `endian` byte order, like the rest of the
+/// session does.
+#[derive(Clone, Copy)]
 pub struct OutputFormat {
     radix: FormatRadix,
     punctuate_number: bool,
+    style: Style,
+    width: u32,
+    endian: Endian,
 }
 
-fn format_with_binary_separators(chars: &[char]) -> String {
-    let mut groups = Vec::new();
-    let mut i = chars.len();
-    while i > 0 {
-        let start;
-        let chunk = if i >= 4 {
-            start = i - 4;
-            &chars[start..i]
-        } else {
-            start = 0;
-            &chars[start..i]
-        };
-        groups.push(chunk.iter().collect::<String>());
-        i = start;
+impl Default for OutputFormat {
+    fn default() -> Self {
+        OutputFormat {
+            radix: FormatRadix::default(),
+            punctuate_number: false,
+            style: Style::default(),
+            width: 64,
+            endian: Endian::default(),
+        }
     }
-    groups.reverse();
-    groups.join("_")
 }
 
 impl OutputFormat {
@@ -118,42 +495,317 @@ impl OutputFormat {
         self
     }
 
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_bytes(mut self, width: u32, endian: Endian) -> Self {
+        self.set_bytes(width, endian);
+        self
+    }
+
     pub fn set_format_radix(&mut self, radix: FormatRadix) {
         self.radix = radix;
     }
 
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    pub fn set_bytes(&mut self, width: u32, endian: Endian) {
+        self.width = width;
+        self.endian = endian;
+    }
+
+    pub fn format_radix(&self) -> FormatRadix {
+        self.radix
+    }
+
     pub fn punctuate_number(&self) -> bool {
         self.punctuate_number
     }
 
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
     pub fn fmt(&self, num: i64) -> String {
-        let (abs_num, negative) = if num < 0 {
-            (-num as u64, true)
+        // timestamps before their epoch are negative, so they keep the sign
+        if let Some(epoch) = self.radix.epoch() {
+            return epoch.fmt(num);
+        }
+        // bytes carry the two's complement form, there is no sign to show
+        if !self.radix.is_numeric() {
+            return self.fmt_unsigned(num as u64);
+        }
+        let formatted = self.fmt_unsigned(num.unsigned_abs());
+        if num < 0 {
+            format!("-{}", formatted)
         } else {
-            (num as u64, false)
+            formatted
+        }
+    }
+
+    /// fmt_unsigned formats the raw bits of a number, for values that are
+    /// meant as addresses or registers rather than signed quantities.
+    pub fn fmt_unsigned(&self, abs_num: u64) -> String {
+        let bytes = || to_bytes(abs_num as i64, self.width, self.endian);
+        match self.radix {
+            FormatRadix::Base64 => return encoding::b64_encode(&bytes()),
+            FormatRadix::HexString => return encoding::hex_encode(&bytes()),
+            FormatRadix::CString => return encoding::cstr_escape(&bytes()),
+            // a varint is the bytes of the number itself, not of its memory layout
+            FormatRadix::Varint => return encoding::hex_encode(&encoding::uleb128_encode(abs_num)),
+            FormatRadix::Char => return unicode::fmt_char(abs_num),
+            FormatRadix::Alphabet(alphabet) => return alphabet.encode(abs_num),
+            FormatRadix::Ipv4 => return net::fmt_ipv4(abs_num),
+            FormatRadix::Ipv6 => return net::fmt_ipv6(abs_num as u128),
+            FormatRadix::Ranges => return bits::ranges(bits::to_unsigned(abs_num as i64, self.width)),
+            FormatRadix::Gray
+            | FormatRadix::Bcd
+            | FormatRadix::UnpackedBcd
+            | FormatRadix::Excess
+            | FormatRadix::OnesComplement => return self.fmt_decoded(bits::to_unsigned(abs_num as i64, self.width)),
+            _ => {}
+        }
+        let radix = match self.radix.radix() {
+            Some(radix) => radix,
+            // timestamps are shown as dates
+            None => return self.fmt(abs_num as i64),
         };
-        let abs_num_chars = self.radix.fmt_uint_to_chars(abs_num);
-        let mut abs_num_str = if self.punctuate_number {
-            uint_with_separators(&abs_num_chars, self.radix)
+        let mut abs_num_chars = uint_to_chars_radix(abs_num, radix);
+        abs_num_chars.reverse();
+        if self.style.zero_pad {
+            let digits = uint_to_chars_radix(bits::range_mask(0, self.width - 1), radix).len();
+            let padding = digits.saturating_sub(abs_num_chars.len());
+            abs_num_chars.splice(0..0, std::iter::repeat_n('0', padding));
+        }
+        if self.style.uppercase {
+            abs_num_chars.iter_mut().for_each(char::make_ascii_uppercase);
+        }
+
+        let group_size = self.style.group_size.unwrap_or(match self.radix {
+            FormatRadix::Decimal | FormatRadix::Octal => 3,
+            _ => 4,
+        });
+        let separator = self.style.separator.unwrap_or(self.style.prefix.separator());
+        let abs_num_str = if self.punctuate_number && group_size > 0 {
+            uint_with_separators(&abs_num_chars, group_size, separator)
         } else {
             String::from_iter(&abs_num_chars)
         };
 
-        if self.radix == FormatRadix::Binary {
-            abs_num_str = format_with_binary_separators(&abs_num_chars);
+        self.style.prefix.mark(self.radix, &abs_num_str)
+    }
+
+    /// fmt_decoded reads the `width` bits of a value as one of the integer
+    /// encodings and shows what it stands for in decimal. Excess-K uses the
+    /// offset binary bias, half the range of the width.
+    fn fmt_decoded(&self, code: u64) -> String {
+        let decoded = match self.radix {
+            FormatRadix::Gray => Ok(encoding::ungray(code).to_string()),
+            FormatRadix::Bcd => encoding::bcd_decode(code, true).map(|value| value.to_string()),
+            FormatRadix::UnpackedBcd => encoding::bcd_decode(code, false).map(|value| value.to_string()),
+            FormatRadix::Excess => encoding::excess_decode(code, 1 << (self.width - 1)).map(|value| value.to_string()),
+            FormatRadix::OnesComplement => Ok(encoding::ones_decode(code, self.width).to_string()),
+            _ => unreachable!("only integer encodings are decoded"),
+        };
+        match decoded {
+            Ok(decoded) => decoded,
+            Err(CorkError::Eval(reason)) => reason,
+            Err(err) => err.to_string(),
+        }
+    }
+}
+
+/// Endian is the byte order values are laid out in memory with.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+impl std::str::FromStr for Endian {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "little" => Ok(Endian::Little),
+            "big" => Ok(Endian::Big),
+            _ => Err(CorkError::InvalidValueForKey {
+                value: s.to_string(),
+                key: "endian".to_string(),
+            }),
         }
+    }
+}
+
+/// to_bytes lays out the lower `width` bits of `value` in memory order.
+pub fn to_bytes(value: i64, width: u32, endian: Endian) -> Vec<u8> {
+    let len = (width / 8) as usize;
+    match endian {
+        Endian::Little => value.to_le_bytes()[..len].to_vec(),
+        Endian::Big => value.to_be_bytes()[8 - len..].to_vec(),
+    }
+}
 
-        let prefix = match self.radix {
-            FormatRadix::Decimal => "0d",
-            FormatRadix::Hex => "0x",
-            FormatRadix::Octal => "0o",
-            FormatRadix::Binary => "0b",
+/// from_bytes reads up to eight bytes in memory order back as a number.
+pub fn from_bytes(bytes: &[u8], endian: Endian) -> Result<i64, CorkError> {
+    if bytes.len() > 8 {
+        return Err(CorkError::Eval(format!("{} bytes do not fit in a number", bytes.len())));
+    }
+    let fold = |acc: u64, &b: &u8| acc << 8 | b as u64;
+    let value = match endian {
+        Endian::Little => bytes.iter().rev().fold(0, fold),
+        Endian::Big => bytes.iter().fold(0, fold),
+    };
+    Ok(value as i64)
+}
+
+/// fmt_bytes renders bytes as text, escaping those that are not printable
+/// ASCII as `\xNN`.
+pub fn fmt_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            b'\\' => "\\\\".to_string(),
+            0x20..=0x7e => (b as char).to_string(),
+            _ => format!("\\x{:02x}", b),
+        })
+        .collect()
+}
+
+/// Cyclic generates de Bruijn sequences: every subsequence of `n` symbols
+/// of the alphabet appears exactly once, so the offset of a few bytes of the
+/// pattern found in a crashed register tells how far an overflow reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cyclic {
+    alphabet: Vec<u8>,
+    n: usize,
+}
+
+impl Default for Cyclic {
+    fn default() -> Self {
+        Cyclic {
+            alphabet: b"abcdefghijklmnopqrstuvwxyz".to_vec(),
+            n: 4,
+        }
+    }
+}
+
+impl Cyclic {
+    pub fn new(alphabet: &str, n: usize) -> Result<Cyclic, CorkError> {
+        let mut symbols = alphabet.as_bytes().to_vec();
+        symbols.sort_unstable();
+        symbols.dedup();
+        if symbols.len() < 2 || symbols.len() != alphabet.len() {
+            return Err(CorkError::InvalidValueForKey {
+                value: alphabet.to_string(),
+                key: "cyclic_alphabet".to_string(),
+            });
+        }
+        if n == 0 {
+            return Err(CorkError::InvalidValueForKey {
+                value: n.to_string(),
+                key: "cyclic_n".to_string(),
+            });
+        }
+        Ok(Cyclic { alphabet: alphabet.as_bytes().to_vec(), n })
+    }
+
+    pub fn alphabet(&self) -> String {
+        String::from_utf8_lossy(&self.alphabet).into_owned()
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// max_len is the length of the full sequence, beyond which subsequences
+    /// would repeat.
+    fn max_len(&self) -> usize {
+        u32::try_from(self.n)
+            .ok()
+            .and_then(|n| self.alphabet.len().checked_pow(n))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// pattern gives the first `len` bytes of the sequence.
+    pub fn pattern(&self, len: usize) -> Result<Vec<u8>, CorkError> {
+        if len > self.max_len() {
+            return Err(CorkError::Eval(format!(
+                "a cyclic pattern of {} symbols with n = {} is at most {} bytes long",
+                self.alphabet.len(),
+                self.n,
+                self.max_len()
+            )));
+        }
+        let mut gen = DeBruijn {
+            cyclic: self,
+            a: vec![0; self.alphabet.len() * self.n + 1],
+            out: Vec::with_capacity(len),
+            limit: len,
         };
+        gen.run(1, 1);
+        gen.out.truncate(len);
+        Ok(gen.out)
+    }
 
-        if negative {
-            format!("-{}{}", prefix, abs_num_str)
-        } else {
-            format!("{}{}", prefix, abs_num_str)
+    /// find gives the offset of the first `n` bytes of `bytes` in the
+    /// sequence.
+    pub fn find(&self, bytes: &[u8]) -> Result<usize, CorkError> {
+        let needle = bytes.get(..self.n).ok_or_else(|| {
+            CorkError::Eval(format!("cyclic_find needs at least {} bytes, found {}", self.n, bytes.len()))
+        })?;
+        let not_found = || CorkError::Eval(format!("{} is not part of the cyclic pattern", fmt_bytes(needle)));
+        if needle.iter().any(|b| !self.alphabet.contains(b)) {
+            return Err(not_found());
+        }
+        // long sequences are searched in growing chunks instead of at once
+        let mut len = 4096.min(self.max_len());
+        loop {
+            let pattern = self.pattern(len)?;
+            if let Some(offset) = pattern.windows(self.n).position(|w| w == needle) {
+                return Ok(offset);
+            }
+            if len == self.max_len() || len >= 1 << 24 {
+                return Err(not_found());
+            }
+            len = len.saturating_mul(16).min(self.max_len());
+        }
+    }
+}
+
+/// DeBruijn is the recursive generator of the sequence (Ruskey's algorithm,
+/// the one pwntools uses), stopped once `limit` bytes are out.
+struct DeBruijn<'a> {
+    cyclic: &'a Cyclic,
+    a: Vec<usize>,
+    out: Vec<u8>,
+    limit: usize,
+}
+
+impl DeBruijn<'_> {
+    fn run(&mut self, t: usize, p: usize) {
+        if self.out.len() >= self.limit {
+            return;
+        }
+        let n = self.cyclic.n;
+        if t > n {
+            if n.is_multiple_of(p) {
+                let symbols = self.a[1..=p].iter().map(|&j| self.cyclic.alphabet[j]);
+                self.out.extend(symbols);
+            }
+            return;
+        }
+        self.a[t] = self.a[t - p];
+        self.run(t + 1, p);
+        for j in self.a[t - p] + 1..self.cyclic.alphabet.len() {
+            self.a[t] = j;
+            self.run(t + 1, t);
         }
     }
 }
@@ -182,4 +834,137 @@ mod test {
             assert_eq!(of.fmt(0), output);
         }
     }
+
+    #[test]
+    fn test_style_fmt() {
+        let cases = [
+            (FormatRadix::Hex, Style { uppercase: true, ..Style::default() }, "0xDEADBEEF"),
+            (FormatRadix::Hex, Style { prefix: Prefix::Suffix, ..Style::default() }, "0deadbeefh"),
+            (FormatRadix::Hex, Style { prefix: Prefix::Dollar, ..Style::default() }, "$deadbeef"),
+            (FormatRadix::Hex, Style { prefix: Prefix::Bare, ..Style::default() }, "deadbeef"),
+            (FormatRadix::Hex, Style { zero_pad: true, ..Style::default() }, "0x00000000deadbeef"),
+            (FormatRadix::Octal, Style { prefix: Prefix::C, ..Style::default() }, "033653337357"),
+            (FormatRadix::Decimal, Style { prefix: Prefix::Python, ..Style::default() }, "3735928559"),
+            (FormatRadix::Binary, Style::default(), "0b11011110101011011011111011101111"),
+        ];
+        for (radix, style, output) in cases {
+            let of = OutputFormat::default().with_format_radix(radix).with_style(style);
+            assert_eq!(of.fmt(0xdeadbeef), output);
+        }
+
+        let of = OutputFormat::default()
+            .with_format_radix(FormatRadix::Decimal)
+            .with_punctuate_number(true)
+            .with_style(Style { prefix: Prefix::C, ..Style::default() });
+        assert_eq!(of.fmt(-1234567), "-1'234'567");
+        let of = OutputFormat::default()
+            .with_format_radix(FormatRadix::Hex)
+            .with_punctuate_number(true)
+            .with_style(Style { separator: Some(' '), group_size: Some(2), ..Style::default() });
+        assert_eq!(of.fmt(0xbeef), "0xbe ef");
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Binary).with_punctuate_number(true);
+        assert_eq!(of.fmt(0x5f), "0b101_1111");
+        assert_eq!(of.with_style(Style { group_size: Some(0), ..Style::default() }).fmt(0x5f), "0b1011111");
+        assert_eq!("$".parse::<Prefix>(), Ok(Prefix::Dollar));
+        assert!("0y".parse::<Prefix>().is_err());
+    }
+
+    #[test]
+    fn test_views() {
+        colored::control::set_override(false);
+        let views: Views = "hex, bin,signed,unsigned".parse().unwrap();
+        let of = OutputFormat::default().with_bytes(8, Endian::Little);
+        assert_eq!(
+            views.table(0xfe, &of),
+            "Hexadecimal: 0xfe\n     Binary: 0b11111110\n     Signed: -0d2\n   Unsigned: 0d254"
+        );
+        assert!("off".parse::<Views>().unwrap().is_empty());
+
+        let all: Views = FormatRadix::radices().map(View::Radix).collect();
+        assert_eq!(all.table(10, &of).lines().count(), 4);
+        assert_eq!(visible_width("\x1b[33mHexadecimal\x1b[0m"), 11);
+        assert!("hex,nope".parse::<Views>().is_err());
+    }
+
+    #[test]
+    fn test_cyclic() {
+        let cyclic = Cyclic::default();
+        let pattern = cyclic.pattern(48).unwrap();
+        assert_eq!(pattern, b"aaaabaaacaaadaaaeaaafaaagaaahaaaiaaajaaakaaalaaa");
+        assert_eq!(cyclic.find(&to_bytes(0x6161616c, 32, Endian::Little)), Ok(44));
+        assert_eq!(cyclic.find(&to_bytes(0x6161616c6161616b, 64, Endian::Little)), Ok(40));
+        assert!(cyclic.find(b"AAAA").is_err());
+
+        let small = Cyclic::new("ab", 3).unwrap();
+        assert_eq!(small.pattern(8).unwrap(), b"aaababbb");
+        assert!(small.pattern(9).is_err());
+        assert!(Cyclic::new("aab", 3).is_err());
+
+        assert_eq!(to_bytes(0x0102, 16, Endian::Big), [1, 2]);
+        assert_eq!(fmt_bytes(b"a\0\\"), "a\\x00\\\\");
+    }
+
+    #[test]
+    fn test_bytes_fmt() {
+        let cases = [
+            (FormatRadix::Base64, "QUJDRA=="),
+            (FormatRadix::HexString, "41424344"),
+            (FormatRadix::CString, "\\x41\\x42\\x43\\x44"),
+        ];
+        for (radix, output) in cases {
+            let of = OutputFormat::default().with_format_radix(radix).with_bytes(32, Endian::Little);
+            assert_eq!(of.fmt(0x44434241), output);
+        }
+        let of = OutputFormat::default().with_format_radix(FormatRadix::HexString).with_bytes(16, Endian::Big);
+        assert_eq!(of.fmt(-2), "fffe");
+        assert_eq!(from_bytes(&to_bytes(-2, 16, Endian::Big), Endian::Big), Ok(0xfffe));
+        assert_eq!(from_bytes(b"AB", Endian::Little), Ok(0x4241));
+        assert!(from_bytes(&[0; 9], Endian::Little).is_err());
+
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Varint);
+        assert_eq!(of.fmt(300), "ac02");
+        assert_eq!(of.fmt(-1), "ffffffffffffffffff01");
+
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Char);
+        assert_eq!(of.fmt(0x41), "U+0041 'A' LATIN CAPITAL LETTER A");
+
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Unix);
+        assert_eq!(of.fmt(-86_400), "1969-12-31T00:00:00Z");
+    }
+
+    #[test]
+    fn test_base_fmt() {
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Base(36));
+        assert_eq!(of.fmt(1295), "0r36:zz");
+        assert_eq!(of.fmt(-5), "-0r36:5");
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Alphabet(Alphabet::Base58));
+        assert_eq!(of.fmt(58), "21");
+        assert_eq!(FormatRadix::from_base("base3"), Ok(FormatRadix::Base(3)));
+        assert_eq!(FormatRadix::from_base("base16"), Ok(FormatRadix::Hex));
+        assert_eq!(FormatRadix::from_base("crockford32"), Ok(FormatRadix::Alphabet(Alphabet::Crockford32)));
+        assert!(FormatRadix::from_base("base37").is_err());
+        assert!(FormatRadix::from_base("base1").is_err());
+        // only formats written in digits have a radix
+        assert_eq!(FormatRadix::Base(3).radix(), Some(3));
+        assert_eq!(FormatRadix::Octal.radix(), Some(8));
+        assert_eq!(FormatRadix::Base64.radix(), None);
+        assert_eq!(FormatRadix::Ipv6.radix(), None);
+    }
+
+    #[test]
+    fn test_decoded_fmt() {
+        let cases = [
+            (FormatRadix::Gray, 0x3, "2"),
+            (FormatRadix::Bcd, 0x1234, "34"),
+            (FormatRadix::Bcd, 0x1a, "0x1a is not packed BCD"),
+            (FormatRadix::UnpackedBcd, 0x0a, "0xa is not unpacked BCD"),
+            (FormatRadix::Excess, 0x7c, "-4"),
+            (FormatRadix::OnesComplement, 0xfe, "-1"),
+            (FormatRadix::OnesComplement, -2, "-1"),
+        ];
+        for (radix, value, output) in cases {
+            let of = OutputFormat::default().with_format_radix(radix).with_bytes(8, Endian::Little);
+            assert_eq!(of.fmt(value), output, "{:?}", radix);
+        }
+    }
 }
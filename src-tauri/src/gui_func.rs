@@ -1,8 +1,19 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use crate::{
-    format::{FormatRadix, OutputFormat},
-    expression,
+    bits::{self, BitEdit},
+    config::Config,
+    error::CorkError,
+    format::{fmt_bytes, to_bytes, FormatRadix, OutputFormat},
+    hashes::HashAlgo,
+    expression::{self, eval::Value},
+    history::HistoryEntry,
+    layout::Field,
+    modules::{Location, Module},
+    paging::{PagingScheme, VaParts},
+    session::Session,
+    status::{self, Meaning},
     CONFIG,
+    SESSION,
 };
 
 // Deserialize is needed to accept parameter from TS
@@ -11,34 +22,294 @@ pub struct CalcOptions {
     pub mode: String,
 }
 
+// Results are sent as hex strings, JS numbers can't hold every i64
+#[derive(Debug, Serialize)]
+pub struct HistoryItem {
+    pub id: u64,
+    pub input: String,
+    pub result: String,
+    pub radix: FormatRadix,
+    pub timestamp: u64,
+    pub pinned: bool,
+}
+
+// The current value of the session together with the width it is shown in
+#[derive(Debug, Serialize)]
+pub struct ValueResult {
+    pub value: String,
+    pub width: u32,
+}
+
+// A number is sent as a hex string, bytes and text as they are shown
+#[derive(Debug, Serialize)]
+pub struct EvalResult {
+    pub value: Option<String>,
+    pub text: Option<String>,
+}
+
+// Module bases are sent as hex strings for the same reason
+#[derive(Debug, Serialize)]
+pub struct ModuleItem {
+    pub name: String,
+    pub base: String,
+    pub image_base: String,
+    pub size: String,
+}
+
+// The offset of a location is sent as a hex string too
+#[derive(Debug, Serialize)]
+pub struct LocationItem {
+    pub module: String,
+    pub offset: String,
+    pub image: bool,
+}
+
+impl From<&Module> for ModuleItem {
+    fn from(module: &Module) -> Self {
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Hex);
+        ModuleItem {
+            name: module.name().clone(),
+            base: of.fmt_unsigned(module.base() as u64),
+            image_base: of.fmt_unsigned(module.image_base() as u64),
+            size: of.fmt_unsigned(module.size() as u64),
+        }
+    }
+}
+
+impl From<Location> for LocationItem {
+    fn from(location: Location) -> Self {
+        LocationItem {
+            module: location.module,
+            offset: OutputFormat::default().with_format_radix(FormatRadix::Hex).fmt_unsigned(location.offset as u64),
+            image: location.image,
+        }
+    }
+}
+
+impl From<&HistoryEntry> for HistoryItem {
+    fn from(entry: &HistoryEntry) -> Self {
+        HistoryItem {
+            id: entry.id(),
+            input: entry.input().clone(),
+            result: OutputFormat::default()
+                .with_format_radix(FormatRadix::Hex)
+                .fmt(entry.result()),
+            radix: entry.radix(),
+            timestamp: entry.timestamp(),
+            pinned: entry.pinned(),
+        }
+    }
+}
+
 #[tauri::command]
-pub fn evaluate_expression(expr_str: &str, options: CalcOptions) -> Result<String, String> {
+pub fn evaluate_expression(expr_str: &str, options: CalcOptions) -> Result<EvalResult, String> {
     let mut config = CONFIG.lock().unwrap();
+    let mut session = SESSION.lock().unwrap();
     config.set_mode(options.mode);
-    match expression::parse_line(expr_str, &config) {
+    evaluate(expr_str, &config, &mut session)
+}
+
+#[tauri::command]
+pub fn edit_bits(edit: BitEdit, width: u32) -> Result<ValueResult, String> {
+    let mode = CONFIG.lock().unwrap().mode().clone();
+    let mut session = SESSION.lock().unwrap();
+    session.width = bits::check_width(width).map_err(|err| err.to_string())?;
+    let value = session.edit_bits(edit).map_err(|err| err.to_string())?;
+    session.history.record(edit.to_string(), value, FormatRadix::Hex, &mode);
+    if let Err(err) = session.save() {
+        eprintln!("Failed to save evaluation history: {}", err);
+    }
+    Ok(ValueResult {
+        value: OutputFormat::default()
+            .with_format_radix(FormatRadix::Hex)
+            .fmt_unsigned(bits::to_unsigned(value, session.width)),
+        width: session.width,
+    })
+}
+
+#[tauri::command]
+pub fn list_layouts() -> Vec<String> {
+    let session = SESSION.lock().unwrap();
+    session.layouts.names().cloned().collect()
+}
+
+#[tauri::command]
+pub fn layout_fields(name: &str) -> Result<Vec<Field>, String> {
+    let session = SESSION.lock().unwrap();
+    let layout = session.layouts.get(name).map_err(|err| err.to_string())?;
+    Ok(layout.fields().clone())
+}
+
+#[tauri::command]
+pub fn split_va(scheme: Option<PagingScheme>) -> VaParts {
+    let session = SESSION.lock().unwrap();
+    scheme.unwrap_or(session.paging).split(session.ans)
+}
+
+#[tauri::command]
+pub fn build_va(scheme: Option<PagingScheme>, values: Vec<(String, i64)>) -> Result<ValueResult, String> {
+    let mut session = SESSION.lock().unwrap();
+    session.ans = scheme
+        .unwrap_or(session.paging)
+        .build(&values)
+        .map_err(|err| err.to_string())?;
+    Ok(ValueResult {
+        value: OutputFormat::default()
+            .with_format_radix(FormatRadix::Hex)
+            .fmt_unsigned(session.ans as u64),
+        width: session.width,
+    })
+}
+
+#[tauri::command]
+pub fn list_modules() -> Vec<ModuleItem> {
+    let session = SESSION.lock().unwrap();
+    session.modules.iter().map(ModuleItem::from).collect()
+}
+
+#[tauri::command]
+pub fn where_is() -> Option<LocationItem> {
+    let session = SESSION.lock().unwrap();
+    session.modules.locate(session.ans).map(LocationItem::from)
+}
+
+#[tauri::command]
+pub fn what_is() -> Vec<Meaning> {
+    let session = SESSION.lock().unwrap();
+    status::whatis(session.ans)
+}
+
+#[tauri::command]
+pub fn cyclic_pattern(length: usize) -> Result<String, String> {
+    let session = SESSION.lock().unwrap();
+    let pattern = session.cyclic.pattern(length).map_err(|err| err.to_string())?;
+    Ok(fmt_bytes(&pattern))
+}
+
+// The value comes as a hex string, like the results sent to the frontend
+#[tauri::command]
+pub fn cyclic_find(value: &str) -> Result<usize, String> {
+    let session = SESSION.lock().unwrap();
+    let digits = value.trim_start_matches("0x").replace('_', "");
+    let value = u64::from_str_radix(&digits, 16).map_err(|err| err.to_string())? as i64;
+    session
+        .cyclic
+        .find(&to_bytes(value, session.width, session.endian))
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn hash_lookup(algo: &str, value: &str) -> Result<Vec<String>, String> {
+    let mut session = SESSION.lock().unwrap();
+    let algo: HashAlgo = algo.parse().map_err(|err: CorkError| err.to_string())?;
+    let digits = value.trim_start_matches("0x").replace('_', "");
+    let value = u64::from_str_radix(&digits, 16).map_err(|err| err.to_string())? as i64;
+    let (found, _) = session.hash_lookup(algo, value).map_err(|err| err.to_string())?;
+    Ok(found)
+}
+
+#[tauri::command]
+pub fn list_history() -> Vec<HistoryItem> {
+    let session = SESSION.lock().unwrap();
+    session.history.entries().iter().map(HistoryItem::from).collect()
+}
+
+#[tauri::command]
+pub fn search_history(query: &str) -> Vec<HistoryItem> {
+    let session = SESSION.lock().unwrap();
+    session.history.search(query).into_iter().map(HistoryItem::from).collect()
+}
+
+#[tauri::command]
+pub fn reevaluate_history(id: u64) -> Result<EvalResult, String> {
+    let mut config = CONFIG.lock().unwrap().clone();
+    let mut session = SESSION.lock().unwrap();
+    let entry = session.history.get(id).map_err(|err| err.to_string())?;
+    let input = entry.input().clone();
+    // the input is read in the mode it was typed in, not the current one
+    if let Some(mode) = entry.mode() {
+        config.set_mode(mode.clone());
+    }
+    evaluate(&input, &config, &mut session)
+}
+
+#[tauri::command]
+pub fn pin_history(id: u64, pinned: bool) -> Result<(), String> {
+    let mut session = SESSION.lock().unwrap();
+    session.history.set_pinned(id, pinned).map_err(|err| err.to_string())?;
+    session.save().map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn delete_history(id: u64) -> Result<(), String> {
+    let mut session = SESSION.lock().unwrap();
+    session.history.delete(id).map_err(|err| err.to_string())?;
+    session.save().map_err(|err| err.to_string())
+}
+
+fn evaluate(expr_str: &str, config: &Config, session: &mut Session) -> Result<EvalResult, String> {
+    let ans = match expression::parse_line(expr_str, config) {
         Ok(command) => match command {
-            expression::Command::Expr(expr) => match expression::eval::eval_expr(&expr, 0) {
-                Ok(ans) => {
-                    Ok(OutputFormat::default()
-                        .with_format_radix(FormatRadix::Hex)
-                        .with_punctuate_number(*config.punctuate_output())
-                        .fmt(ans))
-                }
-                Err(err) => Err(format!("Failed to evaluate \"{}\": {}", expr_str, err)),
+            // encoded bytes and text are shown as they are, ans keeps the last number
+            expression::Command::Expr(expr) => match expression::eval::eval_value(&expr, &session.context()) {
+                Ok(Value::Int(val)) => val,
+                Ok(value) => return Ok(EvalResult { value: None, text: Some(value.to_string()) }),
+                Err(err) => return Err(format!("Failed to evaluate \"{}\": {}", expr_str, err)),
             },
-            expression::Command::Set(_) => Err("Set directive not allowed in inline-expression".to_string()),
-            expression::Command::Convert(conversion) => match conversion.value(0) {
-                Ok(ans) => {
-                    Ok(OutputFormat::default()
-                        .with_format_radix(FormatRadix::Hex)
-                        .with_punctuate_number(*config.punctuate_output())
-                        .fmt(ans))
-                }
-                Err(err) => Err(format!("Failed to evaluate \"{}\": {}", expr_str, err)),
+            expression::Command::Set(_) => return Err("Set directive not allowed in inline-expression".to_string()),
+            expression::Command::Convert(conversion) => match conversion.value_in(&session.context()) {
+                Ok(Value::Int(val)) => val,
+                Ok(value) => return Ok(EvalResult { value: None, text: Some(value.to_string()) }),
+                Err(err) => return Err(format!("Failed to evaluate \"{}\": {}", expr_str, err)),
             },
-            expression::Command::Empty => Ok("Empty expression!".to_string()),
+            expression::Command::Bits(edit) => session.edit_bits(edit)
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::Decode(decode) => decode.value_with(&session.context())
+                .and_then(|val| session.layouts.get(decode.layout()).map(|_| val))
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::Flags(flags) => flags.value_with(&session.context())
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::Encode(encode) => encode.field_values(&session.context())
+                .and_then(|values| session.layouts.get(encode.layout())?.encode(encode.layout(), &values))
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::Va(va) => va.value_with(&session.context())
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::MkVa(mkva) => mkva.field_values(&session.context())
+                .and_then(|values| mkva.scheme().unwrap_or(session.paging).build(&values))
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::Module(module) => module.values(&session.context())
+                .map(|(base, image_base, size)| session.modules.define(module.name(), base, image_base, size).base())
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::WhereIs(expr) => expression::eval::eval_expr_with(&expr, &session.context())
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::WhatIs(expr) => expression::eval::eval_expr_with(&expr, &session.context())
+                .map_err(|err| format!("Failed to evaluate \"{}\": {}", expr_str, err))?,
+            expression::Command::Cyclic(_) => return Err("Cyclic directive not allowed in GUI".to_string()),
+            expression::Command::HashLookup(_) => return Err("Hashlookup directive not allowed in GUI".to_string()),
+            expression::Command::XorBrute(_) => return Err("Xorbrute directive not allowed in GUI".to_string()),
+            expression::Command::Modules => return Err("Modules directive not allowed in GUI".to_string()),
+            expression::Command::History => return Err("History directive not allowed in GUI".to_string()),
+            expression::Command::Empty => {
+                return Ok(EvalResult { value: None, text: Some("Empty expression!".to_string()) })
+            }
         },
         // Err(err) => Err(format!("Failed to parse \"{}\": {}", expr_str, err)),
-        Err(err) => Err(format!("{}", err)),
+        Err(err) => return Err(format!("{}", err)),
+    };
+    session.ans = ans;
+    session.history.record(expr_str.trim(), ans, FormatRadix::Hex, config.mode());
+    if let Err(err) = session.save() {
+        eprintln!("Failed to save evaluation history: {}", err);
     }
-}
\ No newline at end of file
+    // the frontend reads the result back with BigInt, so the output style of
+    // the config does not apply here
+    Ok(EvalResult {
+        value: Some(
+            OutputFormat::default()
+                .with_format_radix(FormatRadix::Hex)
+                .with_punctuate_number(*config.punctuate_output())
+                .fmt(ans),
+        ),
+        text: None,
+    })
+}
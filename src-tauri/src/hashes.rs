@@ -0,0 +1,293 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+use crate::error::CorkError;
+use anyhow::{Context, Result as AResult};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// A HashAlgo is a checksum or hash over a byte string. Besides the usual
+/// CRCs and non-cryptographic hashes, it covers the rotate-and-add hashes
+/// shellcode uses to find exports by name.
+#[derive(EnumIter, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    Crc8,
+    Crc16,
+    Crc16Ccitt,
+    Crc16Modbus,
+    Crc16Xmodem,
+    Crc32,
+    Crc32c,
+    Adler32,
+    Fnv1a32,
+    Fnv1a64,
+    Djb2,
+    Sdbm,
+    Murmur3_32,
+    Ror13,
+    Ror7,
+    Rol5,
+    Rol7Xor,
+}
+
+/// A CrcSpec follows the parameters of the CRC catalogue: `poly` is
+/// written MSB first, and input and output are reflected together.
+struct CrcSpec {
+    width: u32,
+    poly: u64,
+    init: u64,
+    reflect: bool,
+    xorout: u64,
+}
+
+impl CrcSpec {
+    fn checksum(&self, bytes: &[u8]) -> u64 {
+        let mask = u64::MAX >> (64 - self.width);
+        let mut crc = self.init;
+        if self.reflect {
+            let poly = self.poly.reverse_bits() >> (64 - self.width);
+            for &b in bytes {
+                crc ^= b as u64;
+                for _ in 0..8 {
+                    crc = if crc & 1 != 0 { (crc >> 1) ^ poly } else { crc >> 1 };
+                }
+            }
+        } else {
+            let top = 1u64 << (self.width - 1);
+            for &b in bytes {
+                crc ^= (b as u64) << (self.width - 8);
+                for _ in 0..8 {
+                    crc = if crc & top != 0 { (crc << 1) ^ self.poly } else { crc << 1 };
+                }
+                crc &= mask;
+            }
+        }
+        (crc ^ self.xorout) & mask
+    }
+}
+
+impl HashAlgo {
+    /// name is the function name the algorithm is called by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgo::Crc8 => "crc8",
+            HashAlgo::Crc16 => "crc16",
+            HashAlgo::Crc16Ccitt => "crc16_ccitt",
+            HashAlgo::Crc16Modbus => "crc16_modbus",
+            HashAlgo::Crc16Xmodem => "crc16_xmodem",
+            HashAlgo::Crc32 => "crc32",
+            HashAlgo::Crc32c => "crc32c",
+            HashAlgo::Adler32 => "adler32",
+            HashAlgo::Fnv1a32 => "fnv1a32",
+            HashAlgo::Fnv1a64 => "fnv1a64",
+            HashAlgo::Djb2 => "djb2",
+            HashAlgo::Sdbm => "sdbm",
+            HashAlgo::Murmur3_32 => "murmur3_32",
+            HashAlgo::Ror13 => "ror13",
+            HashAlgo::Ror7 => "ror7",
+            HashAlgo::Rol5 => "rol5",
+            HashAlgo::Rol7Xor => "rol7_xor",
+        }
+    }
+
+    /// bits is the width of the hash values.
+    pub fn bits(&self) -> u32 {
+        match self.crc_spec() {
+            Some(spec) => spec.width,
+            None if *self == HashAlgo::Fnv1a64 => 64,
+            None => 32,
+        }
+    }
+
+    fn crc_spec(&self) -> Option<CrcSpec> {
+        let spec = |width, poly, init, reflect, xorout| Some(CrcSpec { width, poly, init, reflect, xorout });
+        match self {
+            HashAlgo::Crc8 => spec(8, 0x07, 0, false, 0),
+            HashAlgo::Crc16 => spec(16, 0x8005, 0, true, 0),
+            HashAlgo::Crc16Ccitt => spec(16, 0x1021, 0xffff, false, 0),
+            HashAlgo::Crc16Modbus => spec(16, 0x8005, 0xffff, true, 0),
+            HashAlgo::Crc16Xmodem => spec(16, 0x1021, 0, false, 0),
+            HashAlgo::Crc32 => spec(32, 0x04c1_1db7, 0xffff_ffff, true, 0xffff_ffff),
+            HashAlgo::Crc32c => spec(32, 0x1edc_6f41, 0xffff_ffff, true, 0xffff_ffff),
+            _ => None,
+        }
+    }
+
+    /// hash computes the algorithm over `bytes`. Only murmur3_32 takes a seed,
+    /// the other algorithms ignore it.
+    pub fn hash(&self, bytes: &[u8], seed: u32) -> u64 {
+        if let Some(spec) = self.crc_spec() {
+            return spec.checksum(bytes);
+        }
+        match self {
+            HashAlgo::Adler32 => {
+                let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &c| {
+                    let a = (a + c as u32) % 65521;
+                    (a, (b + a) % 65521)
+                });
+                ((b << 16) | a) as u64
+            }
+            HashAlgo::Fnv1a32 => bytes
+                .iter()
+                .fold(0x811c_9dc5u32, |h, &c| (h ^ c as u32).wrapping_mul(0x0100_0193)) as u64,
+            HashAlgo::Fnv1a64 => bytes
+                .iter()
+                .fold(0xcbf2_9ce4_8422_2325u64, |h, &c| (h ^ c as u64).wrapping_mul(0x0100_0000_01b3)),
+            HashAlgo::Djb2 => bytes
+                .iter()
+                .fold(5381u32, |h, &c| h.wrapping_mul(33).wrapping_add(c as u32)) as u64,
+            HashAlgo::Sdbm => bytes.iter().fold(0u32, |h, &c| {
+                (c as u32).wrapping_add(h << 6).wrapping_add(h << 16).wrapping_sub(h)
+            }) as u64,
+            HashAlgo::Murmur3_32 => murmur3_32(bytes, seed) as u64,
+            HashAlgo::Ror13 => bytes.iter().fold(0u32, |h, &c| h.rotate_right(13).wrapping_add(c as u32)) as u64,
+            HashAlgo::Ror7 => bytes.iter().fold(0u32, |h, &c| h.rotate_right(7).wrapping_add(c as u32)) as u64,
+            HashAlgo::Rol5 => bytes.iter().fold(0u32, |h, &c| h.rotate_left(5).wrapping_add(c as u32)) as u64,
+            HashAlgo::Rol7Xor => bytes.iter().fold(0u32, |h, &c| h.rotate_left(7) ^ c as u32) as u64,
+            _ => unreachable!("{} is a CRC", self),
+        }
+    }
+}
+
+fn murmur3_32(bytes: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let scramble = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    let mut h = seed;
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        let k = u32::from_le_bytes(chunk.try_into().unwrap());
+        h = (h ^ scramble(k)).rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let k = tail.iter().rev().fold(0u32, |k, &b| (k << 8) | b as u32);
+        h ^= scramble(k);
+    }
+    h ^= bytes.len() as u32;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+impl Display for HashAlgo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for HashAlgo {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HashAlgo::iter()
+            .find(|algo| algo.name() == s)
+            .ok_or_else(|| CorkError::Eval(format!("{} is not a known hash algorithm", s)))
+    }
+}
+
+/// A Wordlist is a list of names, such as DLL exports, to look hashes up
+/// in. The names are hashed once per algorithm, on the first lookup.
+#[derive(Debug, Default)]
+pub struct Wordlist {
+    names: Vec<String>,
+    index: HashMap<HashAlgo, HashMap<u64, Vec<usize>>>,
+}
+
+impl Wordlist {
+    /// from_lines takes one name per line. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn from_lines(content: &str) -> Wordlist {
+        let names = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+        Wordlist { names, index: HashMap::new() }
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> AResult<Wordlist> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read wordlist {}", path.display()))?;
+        Ok(Wordlist::from_lines(&content))
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// lookup gives the names whose `algo` hash is `value`. Only the low
+    /// bits the algorithm produces are compared, so sign-extended values
+    /// match too.
+    pub fn lookup(&mut self, algo: HashAlgo, value: i64) -> Vec<&str> {
+        let names = &self.names;
+        let index = self.index.entry(algo).or_insert_with(|| {
+            let mut index: HashMap<u64, Vec<usize>> = HashMap::with_capacity(names.len());
+            for (idx, name) in names.iter().enumerate() {
+                index.entry(algo.hash(name.as_bytes(), 0)).or_default().push(idx);
+            }
+            index
+        });
+        let value = value as u64 & (u64::MAX >> (64 - algo.bits()));
+        index
+            .get(&value)
+            .map(|found| found.iter().map(|&idx| names[idx].as_str()).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        let check = b"123456789";
+        let cases = [
+            (HashAlgo::Crc8, 0xf4),
+            (HashAlgo::Crc16, 0xbb3d),
+            (HashAlgo::Crc16Ccitt, 0x29b1),
+            (HashAlgo::Crc16Modbus, 0x4b37),
+            (HashAlgo::Crc16Xmodem, 0x31c3),
+            (HashAlgo::Crc32, 0xcbf4_3926),
+            (HashAlgo::Crc32c, 0xe306_9283),
+        ];
+        for (algo, expected) in cases {
+            assert_eq!(algo.hash(check, 0), expected, "{}", algo);
+        }
+        assert_eq!(HashAlgo::Adler32.hash(b"Wikipedia", 0), 0x11e6_0398);
+        assert_eq!(HashAlgo::Fnv1a32.hash(b"a", 0), 0xe40c_292c);
+        assert_eq!(HashAlgo::Fnv1a64.hash(b"a", 0), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(HashAlgo::Djb2.hash(b"a", 0), 177670);
+        assert_eq!(HashAlgo::Murmur3_32.hash(b"test", 0), 0xba6b_d213);
+        assert_eq!(HashAlgo::Murmur3_32.hash(b"Hello, world!", 1234), 0xfaf6_cdb3);
+    }
+
+    #[test]
+    fn test_api_hashes() {
+        assert_eq!(HashAlgo::Ror13.hash(b"LoadLibraryA", 0), 0xec0e_4e8e);
+        assert_eq!(HashAlgo::Ror13.hash(b"GetProcAddress", 0), 0x7c0d_fcaa);
+        for algo in HashAlgo::iter() {
+            assert_eq!(algo.name().parse::<HashAlgo>(), Ok(algo));
+        }
+    }
+
+    #[test]
+    fn test_wordlist_lookup() {
+        let mut wordlist = Wordlist::from_lines("# kernel32\nLoadLibraryA\n\nGetProcAddress\n  ExitProcess \n");
+        assert_eq!(wordlist.len(), 3);
+        assert_eq!(wordlist.lookup(HashAlgo::Ror13, 0xec0e_4e8e), ["LoadLibraryA"]);
+        assert_eq!(wordlist.lookup(HashAlgo::Ror13, 0x7c0d_fcaau32 as i32 as i64), ["GetProcAddress"]);
+        let crc = HashAlgo::Crc32.hash(b"ExitProcess", 0) as i64;
+        assert_eq!(wordlist.lookup(HashAlgo::Crc32, crc), ["ExitProcess"]);
+        assert!(wordlist.lookup(HashAlgo::Djb2, crc).is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{error::CorkError, format::FormatRadix};
+use anyhow::Result as AResult;
+use getset::{CopyGetters, Getters};
+use serde::{Deserialize, Serialize};
+
+/// Unpinned entries beyond this count are dropped, oldest first.
+const MAX_ENTRIES: usize = 1000;
+
+/// A HistoryEntry is one successful evaluation, as typed by the user.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Getters, CopyGetters)]
+pub struct HistoryEntry {
+    #[getset(get_copy = "pub")]
+    id: u64,
+    #[getset(get = "pub")]
+    input: String,
+    #[getset(get_copy = "pub")]
+    result: i64,
+    #[getset(get_copy = "pub")]
+    radix: FormatRadix,
+    #[getset(get_copy = "pub")]
+    timestamp: u64,
+    #[getset(get_copy = "pub")]
+    pinned: bool,
+    /// The mode the input was read in, missing in entries from older files.
+    #[serde(default)]
+    #[getset(get = "pub")]
+    mode: Option<String>,
+}
+
+/// History keeps the evaluations of a session. Entry ids start at 1 and are
+/// never reused, so `$3` keeps pointing at the same result after deletions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct History {
+    next_id: u64,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn load<T: AsRef<Path>>(path: T) -> AResult<History> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Ok(History::default());
+        }
+        let mut content = String::new();
+        File::open(path)?.read_to_string(&mut content)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> AResult<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// record appends a new entry and returns its id.
+    pub fn record<T: Into<String>>(&mut self, input: T, result: i64, radix: FormatRadix, mode: &str) -> u64 {
+        self.next_id = self.next_id.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.entries.push(HistoryEntry {
+            id,
+            input: input.into(),
+            result,
+            radix,
+            timestamp,
+            pinned: false,
+            mode: Some(mode.to_string()),
+        });
+        self.trim();
+        id
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, id: u64) -> Result<&HistoryEntry, CorkError> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .ok_or(CorkError::NoHistoryEntry(id))
+    }
+
+    /// search returns the entries whose input or result contains `query`.
+    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        let query = query.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry.input.to_lowercase().contains(&query)
+                    || format!("{:x}", entry.result).contains(&query)
+                    || entry.result.to_string().contains(&query)
+            })
+            .collect()
+    }
+
+    pub fn set_pinned(&mut self, id: u64, pinned: bool) -> Result<(), CorkError> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or(CorkError::NoHistoryEntry(id))?;
+        entry.pinned = pinned;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: u64) -> Result<(), CorkError> {
+        let idx = self
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or(CorkError::NoHistoryEntry(id))?;
+        self.entries.remove(idx);
+        Ok(())
+    }
+
+    fn trim(&mut self) {
+        let mut excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.retain(|entry| {
+            if excess > 0 && !entry.pinned {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_history_ids_survive_delete() {
+        let mut history = History::default();
+        assert_eq!(history.record("1 + 1", 2, FormatRadix::Hex, "hex"), 1);
+        assert_eq!(history.record("2 + 2", 4, FormatRadix::Hex, "hex"), 2);
+        history.delete(1).unwrap();
+        assert_eq!(history.record("3 + 3", 6, FormatRadix::Decimal, "hex"), 3);
+        assert_eq!(history.get(3).unwrap().result(), 6);
+        assert_eq!(history.get(1), Err(CorkError::NoHistoryEntry(1)));
+        assert_eq!(history.search("2 +").len(), 1);
+        assert_eq!(history.get(3).unwrap().mode().as_deref(), Some("hex"));
+
+        // entries saved before modes were kept have none
+        let entry: HistoryEntry = serde_json::from_str(
+            r#"{"id":1,"input":"ff","result":255,"radix":"Hex","timestamp":0,"pinned":false}"#,
+        )
+        .unwrap();
+        assert_eq!(entry.mode(), &None);
+    }
+
+    #[test]
+    fn test_history_trim_keeps_pinned() {
+        let mut history = History::default();
+        history.record("first", 1, FormatRadix::Hex, "hex");
+        history.set_pinned(1, true).unwrap();
+        for i in 0..MAX_ENTRIES {
+            history.record("filler", i as i64, FormatRadix::Hex, "hex");
+        }
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert!(history.get(1).unwrap().pinned());
+        assert!(history.get(2).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::error::CorkError;
+
+const B_OPCODE: u32 = 0x1400_0000;
+const BL_OPCODE: u32 = 0x9400_0000;
+const B_OPCODE_MASK: u32 = 0x7c00_0000;
+const IMM26_MASK: u32 = 0x03ff_ffff;
+
+/// in_range checks that `value` fits in a signed field of `bits` bits.
+fn in_range(value: i64, bits: u32) -> bool {
+    let limit = 1i64 << (bits - 1);
+    (-limit..limit).contains(&value)
+}
+
+/// word reads a 32 bit instruction or constant, given unsigned or sign
+/// extended.
+pub fn word(value: i64) -> Result<u32, CorkError> {
+    u32::try_from(value)
+        .or_else(|_| i32::try_from(value).map(|value| value as u32))
+        .map_err(|_| CorkError::Eval(format!("{:#x} does not fit in 32 bits", value)))
+}
+
+/// rel32 gives the displacement of an x86 jmp, call or jcc at `from` to
+/// `to`. It is counted from the end of the instruction, `len` bytes on: 5
+/// for jmp and call, 6 for jcc.
+pub fn rel32(from: i64, to: i64, len: i64) -> Result<i64, CorkError> {
+    let disp = to.wrapping_sub(from.wrapping_add(len));
+    if !in_range(disp, 32) {
+        return Err(CorkError::Eval(format!("{:#x} is out of rel32 range of {:#x}", to, from)));
+    }
+    Ok(disp)
+}
+
+/// adrp gives the page delta an ARM64 adrp at `from` needs to reach the
+/// 4 KiB page of `to`, within 4 GiB.
+pub fn adrp(from: i64, to: i64) -> Result<i64, CorkError> {
+    let delta = (to >> 12).wrapping_sub(from >> 12);
+    if !in_range(delta, 21) {
+        return Err(CorkError::Eval(format!("{:#x} is out of adrp range of {:#x}", to, from)));
+    }
+    Ok(delta)
+}
+
+/// encode_b gives the ARM64 b, or bl with `link`, at `from` to `to`. The
+/// offset is in words, within 128 MiB.
+pub fn encode_b(from: i64, to: i64, link: bool) -> Result<u32, CorkError> {
+    let offset = to.wrapping_sub(from);
+    if offset % 4 != 0 {
+        return Err(CorkError::Eval(format!("{:#x} is not word aligned with {:#x}", to, from)));
+    }
+    if !in_range(offset / 4, 26) {
+        return Err(CorkError::Eval(format!("{:#x} is out of b range of {:#x}", to, from)));
+    }
+    let opcode = if link { BL_OPCODE } else { B_OPCODE };
+    Ok(opcode | (offset / 4) as u32 & IMM26_MASK)
+}
+
+/// decode_b gives the target of the ARM64 b or bl `insn` at `at`.
+pub fn decode_b(insn: u32, at: i64) -> Result<i64, CorkError> {
+    if insn & B_OPCODE_MASK != B_OPCODE {
+        return Err(CorkError::Eval(format!("{:#010x} is not a b or bl instruction", insn)));
+    }
+    // shift the sign bit of imm26 to the top and back to extend it
+    let offset = (((insn & IMM26_MASK) << 6) as i32 >> 6) as i64 * 4;
+    Ok(at.wrapping_add(offset))
+}
+
+/// armimm gives the 12 bit ARM32 modified immediate for `value`, an 8 bit
+/// constant rotated right by an even amount, if it has one. The smallest
+/// rotation is used, as assemblers do.
+pub fn armimm(value: u32) -> Option<u32> {
+    (0..16).find_map(|rot| {
+        let imm8 = value.rotate_left(2 * rot);
+        (imm8 <= 0xff).then_some(rot << 8 | imm8)
+    })
+}
+
+/// armimm_decode gives the value of a 12 bit ARM32 modified immediate.
+pub fn armimm_decode(encoded: u32) -> Result<u32, CorkError> {
+    if encoded > 0xfff {
+        return Err(CorkError::Eval(format!("{:#x} is not a 12 bit modified immediate", encoded)));
+    }
+    Ok((encoded & 0xff).rotate_right(2 * (encoded >> 8)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_x86_and_adrp() {
+        assert_eq!(rel32(0x401000, 0x401100, 5).unwrap(), 0xfb);
+        assert_eq!(rel32(0x401100, 0x401000, 6).unwrap(), -0x106);
+        assert!(rel32(0x1000, 0x1_0000_1000, 5).is_err());
+        assert_eq!(adrp(0x4000_1234, 0x4012_3456).unwrap(), 0x122);
+        assert_eq!(adrp(0x4012_3456, 0x4000_1234).unwrap(), -0x122);
+        assert!(adrp(0, 0x1_0000_0000).is_err());
+    }
+
+    #[test]
+    fn test_arm_branches() {
+        assert_eq!(encode_b(0x1000, 0x2000, false).unwrap(), 0x1400_0400);
+        assert_eq!(encode_b(0x2000, 0x1000, true).unwrap(), 0x97ff_fc00);
+        assert_eq!(decode_b(0x97ff_fc00, 0x2000).unwrap(), 0x1000);
+        assert_eq!(decode_b(0x1400_0400, 0x1000).unwrap(), 0x2000);
+        assert!(encode_b(0, 0x800_0000, false).is_err());
+        assert!(encode_b(0, 0x7ff_fffc, false).is_ok());
+        assert!(encode_b(0, 2, false).is_err());
+        assert!(decode_b(0xd503_201f, 0).is_err());
+    }
+
+    #[test]
+    fn test_armimm() {
+        assert_eq!(armimm(0xff), Some(0xff));
+        assert_eq!(armimm(0xff00_0000), Some(0x4ff));
+        assert_eq!(armimm(0x3fc), Some(0xfff));
+        assert_eq!(armimm(0x101), None);
+        assert_eq!(armimm(0x1fe), None);
+        assert_eq!(armimm(0xf000_000f), Some(0x2ff));
+        assert_eq!(armimm_decode(0x4ff).unwrap(), 0xff00_0000);
+        assert_eq!(armimm_decode(0xfff).unwrap(), 0x3fc);
+        assert!(armimm_decode(0x1000).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::Read,
+    path::Path,
+};
+
+use crate::{
+    bits::{check_width, range_mask, to_signed, to_unsigned},
+    error::CorkError,
+    format::{align_columns, FormatRadix, OutputFormat},
+};
+use anyhow::Result as AResult;
+use once_cell::sync::Lazy;
+use getset::{CopyGetters, Getters};
+use serde::{Deserialize, Serialize};
+
+/// A BitSpan is an inclusive range of bit positions. In YAML it is written
+/// either as a single bit (`5`) or as a range (`12..15`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, CopyGetters)]
+#[serde(try_from = "BitSpanSpec")]
+#[getset(get_copy = "pub")]
+pub struct BitSpan {
+    lo: u32,
+    hi: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BitSpanSpec {
+    Bit(u32),
+    Range(String),
+}
+
+impl TryFrom<BitSpanSpec> for BitSpan {
+    type Error = String;
+
+    fn try_from(spec: BitSpanSpec) -> Result<Self, Self::Error> {
+        let (lo, hi) = match spec {
+            BitSpanSpec::Bit(bit) => (bit, bit),
+            BitSpanSpec::Range(range) => {
+                let parse = |s: &str| {
+                    s.trim()
+                        .parse::<u32>()
+                        .map_err(|_| format!("{} is not a valid bit range", range))
+                };
+                match range.split_once("..") {
+                    Some((lo, hi)) => (parse(lo)?, parse(hi)?),
+                    None => (parse(&range)?, parse(&range)?),
+                }
+            }
+        };
+        BitSpan::new(lo, hi).ok_or_else(|| format!("{}..{} is not a valid bit range", lo, hi))
+    }
+}
+
+impl BitSpan {
+    pub fn new(lo: u32, hi: u32) -> Option<BitSpan> {
+        if lo <= hi && hi < 64 {
+            Some(BitSpan { lo, hi })
+        } else {
+            None
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        range_mask(self.lo, self.hi)
+    }
+
+    fn extract(&self, bits: u64) -> u64 {
+        (bits & self.mask()) >> self.lo
+    }
+}
+
+impl Display for BitSpan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.lo == self.hi {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "{}..{}", self.lo, self.hi)
+        }
+    }
+}
+
+/// A Field is a named group of bits inside a layout.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Getters, CopyGetters)]
+pub struct Field {
+    #[getset(get = "pub")]
+    name: String,
+    #[getset(get_copy = "pub")]
+    bits: BitSpan,
+    #[serde(default)]
+    #[getset(get = "pub")]
+    description: String,
+}
+
+/// A Layout describes the bit fields of a register or table entry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Getters, CopyGetters)]
+pub struct Layout {
+    #[serde(default = "default_width")]
+    #[getset(get_copy = "pub")]
+    width: u32,
+    #[getset(get = "pub")]
+    fields: Vec<Field>,
+}
+
+fn default_width() -> u32 {
+    64
+}
+
+impl Layout {
+    fn field(&self, name: &str) -> Option<&Field> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+    }
+
+    /// encode builds a value from `(field, value)` pairs. Fields that are not
+    /// given are zero.
+    pub fn encode(&self, layout: &str, values: &[(String, i64)]) -> Result<i64, CorkError> {
+        let mut bits = 0u64;
+        for (name, value) in values {
+            let field = self.field(name).ok_or_else(|| CorkError::UnknownField {
+                layout: layout.to_string(),
+                field: name.clone(),
+            })?;
+            let span = field.bits;
+            let value = *value as u64;
+            if value & !(span.mask() >> span.lo) != 0 {
+                return Err(CorkError::FieldOverflow {
+                    field: field.name.clone(),
+                    value: value as i64,
+                    bits: span.hi - span.lo + 1,
+                });
+            }
+            bits = (bits & !span.mask()) | (value << span.lo);
+        }
+        Ok(to_signed(bits, self.width))
+    }
+
+    /// decode renders every field of `value` as an aligned table.
+    pub fn decode(&self, value: i64) -> String {
+        let bits = to_unsigned(value, self.width);
+        let of = OutputFormat::default().with_format_radix(FormatRadix::Hex);
+        let header = ["Field", "Bits", "Value", "Description"].map(String::from).to_vec();
+        let rows: Vec<Vec<String>> = std::iter::once(header)
+            .chain(self.fields.iter().map(|field| {
+                vec![
+                    field.name.clone(),
+                    field.bits.to_string(),
+                    of.fmt_unsigned(field.bits.extract(bits)),
+                    field.description.clone(),
+                ]
+            }))
+            .collect();
+        align_columns(&rows)
+    }
+}
+
+/// Layouts maps lowercase layout names to their definitions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Layouts(BTreeMap<String, Layout>);
+
+static BUILTIN_LAYOUTS: Lazy<Layouts> = Lazy::new(|| {
+    Layouts::from_yaml(include_str!("layouts.yml")).expect("built-in layouts are valid")
+});
+
+impl Layouts {
+    /// builtin gives the layouts shipped with cork: x86-64 control registers,
+    /// RFLAGS and paging entries, ARM64 and RISC-V system registers.
+    pub fn builtin() -> Layouts {
+        BUILTIN_LAYOUTS.clone()
+    }
+
+    /// extend adds the layouts of `other`, replacing those with the same name.
+    pub fn extend(&mut self, other: Layouts) {
+        self.0.extend(other.0);
+    }
+
+    pub fn from_yaml(content: &str) -> AResult<Layouts> {
+        let layouts: BTreeMap<String, Layout> = serde_yaml::from_str(content)?;
+        let mut checked = BTreeMap::new();
+        for (name, layout) in layouts {
+            check_width(layout.width)?;
+            if let Some(field) = layout.fields.iter().find(|field| field.bits.hi >= layout.width) {
+                return Err(CorkError::BitOutOfRange {
+                    bit: field.bits.hi,
+                    width: layout.width,
+                }
+                .into());
+            }
+            checked.insert(name.to_lowercase(), layout);
+        }
+        Ok(Layouts(checked))
+    }
+
+    /// load reads the layouts stored at `path`. A missing file gives no layouts.
+    pub fn load<T: AsRef<Path>>(path: T) -> AResult<Layouts> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Ok(Layouts::default());
+        }
+        let mut content = String::new();
+        File::open(path)?.read_to_string(&mut content)?;
+        Layouts::from_yaml(&content)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Layout, CorkError> {
+        self.0
+            .get(&name.to_lowercase())
+            .ok_or_else(|| CorkError::UnknownLayout(name.to_string()))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CR0: &str = "
+cr0:
+  width: 32
+  fields:
+    - { name: PE, bits: 0, description: Protection Enable }
+    - { name: MP, bits: 1, description: Monitor Coprocessor }
+    - { name: ET, bits: 4 }
+    - { name: PG, bits: 31, description: Paging }
+nibble:
+  width: 8
+  fields:
+    - { name: lo, bits: 0..3 }
+    - { name: hi, bits: 4..7 }
+";
+
+    #[test]
+    fn test_layout_encode_decode() {
+        let layouts = Layouts::from_yaml(CR0).unwrap();
+        let cr0 = layouts.get("CR0").unwrap();
+        let value = cr0
+            .encode("cr0", &[("pe".to_string(), 1), ("PG".to_string(), 1)])
+            .unwrap();
+        assert_eq!(value, i32::MIN as i64 + 1);
+        let table = cr0.decode(value);
+        assert_eq!(
+            table.lines().collect::<Vec<_>>(),
+            [
+                "Field  Bits  Value  Description",
+                "PE     0     0x1    Protection Enable",
+                "MP     1     0x0    Monitor Coprocessor",
+                "ET     4     0x0",
+                "PG     31    0x1    Paging",
+            ]
+        );
+
+        let nibble = layouts.get("nibble").unwrap();
+        assert_eq!(
+            nibble.encode("nibble", &[("hi".to_string(), 0x10)]),
+            Err(CorkError::FieldOverflow { field: "hi".to_string(), value: 0x10, bits: 4 })
+        );
+        assert_eq!(
+            nibble.encode("nibble", &[("mid".to_string(), 1)]),
+            Err(CorkError::UnknownField { layout: "nibble".to_string(), field: "mid".to_string() })
+        );
+        assert_eq!(layouts.get("efer"), Err(CorkError::UnknownLayout("efer".to_string())));
+    }
+
+    #[test]
+    fn test_builtin_layouts() {
+        let mut layouts = Layouts::builtin();
+        for name in ["cr0", "cr3", "cr3_pcid", "cr4", "efer", "rflags", "pml5e", "pml4e", "pdpte", "pde", "pte", "ttbr", "tcr", "sctlr", "esr", "satp", "mstatus"] {
+            assert!(layouts.get(name).is_ok(), "missing built-in layout {}", name);
+        }
+        // the architectural names of the ARM64 registers
+        for (name, short) in [("ttbr0_el1", "ttbr"), ("ttbr1_el1", "ttbr"), ("tcr_el1", "tcr"), ("sctlr_el1", "sctlr"), ("esr_el1", "esr")] {
+            assert_eq!(layouts.get(name), layouts.get(short), "{}", name);
+        }
+        for (name, layout) in &layouts.0 {
+            let mut used = 0;
+            for field in layout.fields() {
+                assert_eq!(used & field.bits().mask(), 0, "{} overlaps in {}", field.name(), name);
+                used |= field.bits().mask();
+            }
+        }
+        let pte = layouts.get("pte").unwrap();
+        let fields = pte.decode(0x8000000012345067u64 as i64);
+        assert!(fields.contains("PFN     12..51  0x12345"), "{}", fields);
+        assert!(fields.contains("XD      63      0x1"), "{}", fields);
+        assert_eq!(
+            pte.encode("pte", &[("P".to_string(), 1), ("PFN".to_string(), 0x12345), ("XD".to_string(), 1)]),
+            Ok(0x8000000012345001u64 as i64)
+        );
+
+        layouts.extend(Layouts::from_yaml(CR0).unwrap());
+        assert_eq!(layouts.get("cr0").unwrap().width(), 32);
+    }
+
+    #[test]
+    fn test_layout_invalid_bits() {
+        assert!(Layouts::from_yaml("r:\n  width: 8\n  fields:\n    - { name: x, bits: 8 }").is_err());
+        assert!(Layouts::from_yaml("r:\n  fields:\n    - { name: x, bits: 5..2 }").is_err());
+    }
+}
@@ -2,16 +2,33 @@ use clap::Parser;
 use config::{read_config, Config};
 use std::process::exit;
 use crate::options::Options;
+use crate::session::Session;
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
 
+mod bits;
 mod config;
 mod error;
 mod expression;
 mod format;
 mod options;
 mod cmd;
+mod encoding;
+mod filter;
+mod flags;
 mod gui_func;
+mod hashes;
+mod history;
+mod insn;
+mod layout;
+mod modules;
+mod net;
+mod paging;
+mod report;
+mod session;
+mod status;
+mod timestamp;
+mod unicode;
 
 static CONFIG: Lazy<Mutex<Config>> = Lazy::new(|| {
     let options = Options::parse();
@@ -25,13 +42,32 @@ static CONFIG: Lazy<Mutex<Config>> = Lazy::new(|| {
     Mutex::new(config)
 });
 
+static SESSION: Lazy<Mutex<Session>> = Lazy::new(|| {
+    let options = Options::parse();
+    let mut session = match Session::load(options.config.as_ref()) {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("Failed to load the session: {}", err);
+            exit(1);
+        }
+    };
+    // CONFIG may be locked by the caller, so the config is read on its own
+    let configured = read_config(options.config.as_ref())
+        .and_then(|config| Ok(session.configure(&config)?));
+    if let Err(err) = configured {
+        eprintln!("Failed to parse config: {}", err);
+        exit(1);
+    }
+    Mutex::new(session)
+});
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // 使用已有的 Options 替代 Cli
     let options = Options::parse();
     
     // 当有任何命令行参数时，进入命令行模式
-    if options.expr.is_some() || options.file.is_some() || options.interactive {
+    if options.expr.is_some() || options.file.is_some() || options.stdin.is_some() || options.interactive {
         let config = CONFIG.lock().unwrap();
         cmd::cmd_main((*config).clone(), options);
         return;
@@ -39,7 +75,25 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![gui_func::evaluate_expression])
+        .invoke_handler(tauri::generate_handler![
+            gui_func::evaluate_expression,
+            gui_func::edit_bits,
+            gui_func::list_history,
+            gui_func::search_history,
+            gui_func::reevaluate_history,
+            gui_func::pin_history,
+            gui_func::delete_history,
+            gui_func::list_layouts,
+            gui_func::layout_fields,
+            gui_func::split_va,
+            gui_func::build_va,
+            gui_func::list_modules,
+            gui_func::where_is,
+            gui_func::what_is,
+            gui_func::cyclic_pattern,
+            gui_func::cyclic_find,
+            gui_func::hash_lookup,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
\ No newline at end of file
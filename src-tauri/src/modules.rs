@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use crate::error::CorkError;
+use getset::{CopyGetters, Getters};
+use serde::Serialize;
+
+/// A Module is an image loaded at `base` in the live process. `image_base`
+/// is the base it was linked at, as shown by a disassembler; it is zero when
+/// only the live base is known, so image addresses are then plain RVAs.
+/// `size` is zero when it is not known, the module then reaches up to the
+/// next module base.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Getters, CopyGetters)]
+pub struct Module {
+    #[getset(get = "pub")]
+    name: String,
+    #[getset(get_copy = "pub")]
+    base: i64,
+    #[getset(get_copy = "pub")]
+    image_base: i64,
+    #[getset(get_copy = "pub")]
+    size: i64,
+}
+
+impl Module {
+    /// rebase moves an address from the image into the live process.
+    pub fn rebase(&self, addr: i64) -> i64 {
+        addr.wrapping_sub(self.image_base).wrapping_add(self.base)
+    }
+
+    /// rva gives the offset of a live address from the module base.
+    pub fn rva(&self, addr: i64) -> i64 {
+        addr.wrapping_sub(self.base)
+    }
+
+    /// offset gives the offset of `addr` from `base` when it falls inside
+    /// the module loaded there, or anywhere above `base` when the size of
+    /// the module is not known.
+    fn offset(&self, base: i64, addr: i64) -> Option<i64> {
+        let offset = (addr as u64).checked_sub(base as u64)?;
+        (self.size == 0 || offset < self.size as u64).then_some(offset as i64)
+    }
+}
+
+/// A Location tells which module an address falls in. `image` is set when
+/// the address was found relative to the image base rather than the live one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub module: String,
+    pub offset: i64,
+    pub image: bool,
+}
+
+/// Modules maps lowercase module names to their bases.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modules(BTreeMap<String, Module>);
+
+impl Modules {
+    /// define adds a module, replacing any module of the same name.
+    pub fn define(&mut self, name: &str, base: i64, image_base: Option<i64>, size: Option<i64>) -> &Module {
+        let key = name.to_lowercase();
+        self.0.insert(
+            key.clone(),
+            Module {
+                name: name.to_string(),
+                base,
+                image_base: image_base.unwrap_or(0),
+                size: size.unwrap_or(0),
+            },
+        );
+        &self.0[&key]
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Module, CorkError> {
+        self.0
+            .get(&name.to_lowercase())
+            .ok_or_else(|| CorkError::UnknownModule(name.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Module> {
+        self.0.values()
+    }
+
+    /// locate finds the module `addr` falls in, between its base and its
+    /// base plus its size, or the closest base below `addr` for a module of
+    /// unknown size. Live bases are looked at before image bases.
+    pub fn locate(&self, addr: i64) -> Option<Location> {
+        let inside = |base: fn(&Module) -> i64| {
+            self.iter()
+                .filter(|module| base(module) != 0)
+                .filter_map(|module| module.offset(base(module), addr).map(|offset| (module, offset)))
+                .min_by_key(|(_, offset)| *offset as u64)
+        };
+        if let Some((module, offset)) = inside(Module::base) {
+            return Some(Location { module: module.name.clone(), offset, image: false });
+        }
+        inside(Module::image_base).map(|(module, offset)| Location { module: module.name.clone(), offset, image: true })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_module_rebase() {
+        let mut modules = Modules::default();
+        modules.define("ntdll", 0x7ffb_1230_0000, Some(0x1_8000_0000), Some(0x1f_0000));
+        modules.define("app", 0x7ff6_0000_0000, Some(0x1_4000_0000), Some(0x10_0000));
+        let ntdll = modules.get("NTDLL").unwrap();
+        assert_eq!(ntdll.rebase(0x1_8000_1234), 0x7ffb_1230_1234);
+        assert_eq!(ntdll.rva(0x7ffb_1230_1234), 0x1234);
+        assert_eq!(modules.get("kernel32"), Err(CorkError::UnknownModule("kernel32".to_string())));
+
+        assert_eq!(
+            modules.locate(0x7ffb_1230_1234),
+            Some(Location { module: "ntdll".to_string(), offset: 0x1234, image: false })
+        );
+        assert_eq!(
+            modules.locate(0x1_4000_2000),
+            Some(Location { module: "app".to_string(), offset: 0x2000, image: true })
+        );
+        // past the end of a module
+        assert_eq!(modules.locate(0x7ffb_1250_0000), None);
+        assert_eq!(modules.locate(-1), None);
+    }
+
+    #[test]
+    fn test_module_locate_unsized() {
+        let mut modules = Modules::default();
+        modules.define("ntdll", 0x7ffb_1230_0000, Some(0x1_8000_0000), None);
+        modules.define("app", 0x7ff6_0000_0000, None, None);
+        // without a size, the closest base at or below the address is taken
+        assert_eq!(
+            modules.locate(0x7ffb_1240_0000),
+            Some(Location { module: "ntdll".to_string(), offset: 0x10_0000, image: false })
+        );
+        assert_eq!(
+            modules.locate(0x7ff6_0000_1000),
+            Some(Location { module: "app".to_string(), offset: 0x1000, image: false })
+        );
+        assert_eq!(
+            modules.locate(0x1_8000_2000),
+            Some(Location { module: "ntdll".to_string(), offset: 0x2000, image: true })
+        );
+        assert_eq!(modules.locate(0x1000), None);
+    }
+}
@@ -0,0 +1,97 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::error::CorkError;
+
+/// parse_ipv4 reads a dotted quad like `192.168.0.1` as its 32 bit value.
+pub fn parse_ipv4(text: &str) -> Result<u32, CorkError> {
+    text.parse::<Ipv4Addr>()
+        .map(u32::from)
+        .map_err(|_| CorkError::Eval(format!("{} is not an IPv4 address", text)))
+}
+
+/// parse_ipv6 reads an IPv6 address, `::` and a trailing dotted quad
+/// included, as its 128 bit value.
+pub fn parse_ipv6(text: &str) -> Result<u128, CorkError> {
+    text.parse::<Ipv6Addr>()
+        .map(u128::from)
+        .map_err(|_| CorkError::Eval(format!("{} is not an IPv6 address", text)))
+}
+
+/// fmt_ipv4 shows a value as a dotted quad, if it fits in 32 bits.
+pub fn fmt_ipv4(value: u64) -> String {
+    match u32::try_from(value) {
+        Ok(addr) => Ipv4Addr::from(addr).to_string(),
+        Err(_) => format!("{:#x} is not an IPv4 address", value),
+    }
+}
+
+/// fmt_ipv6 shows a value as an IPv6 address.
+pub fn fmt_ipv6(value: u128) -> String {
+    Ipv6Addr::from(value).to_string()
+}
+
+/// netmask gives the IPv4 mask with the top `prefix` bits set.
+pub fn netmask(prefix: i64) -> Result<u32, CorkError> {
+    match prefix {
+        0 => Ok(0),
+        1..=32 => Ok(u32::MAX << (32 - prefix)),
+        _ => Err(CorkError::Eval(format!("/{} is not an IPv4 prefix", prefix))),
+    }
+}
+
+/// netmask6 gives the IPv6 mask with the top `prefix` bits set.
+pub fn netmask6(prefix: i64) -> Result<u128, CorkError> {
+    match prefix {
+        0 => Ok(0),
+        1..=128 => Ok(u128::MAX << (128 - prefix)),
+        _ => Err(CorkError::Eval(format!("/{} is not an IPv6 prefix", prefix))),
+    }
+}
+
+/// hosts counts the addresses an IPv4 prefix leaves for hosts. The network
+/// and broadcast addresses are not usable, except in /31 point-to-point
+/// links (RFC 3021) and /32 single hosts.
+pub fn hosts(prefix: i64) -> Result<u64, CorkError> {
+    netmask(prefix)?;
+    Ok(match prefix {
+        32 => 1,
+        31 => 2,
+        _ => (1u64 << (32 - prefix)) - 2,
+    })
+}
+
+/// ipv4 checks that a number is an IPv4 address.
+pub fn ipv4(value: i64) -> Result<u32, CorkError> {
+    u32::try_from(value).map_err(|_| CorkError::Eval(format!("{:#x} is not an IPv4 address", value)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(parse_ipv4("192.168.1.10").unwrap(), 0xc0a8_010a);
+        assert!(parse_ipv4("256.0.0.1").is_err());
+        assert_eq!(fmt_ipv4(0xc0a8_010a), "192.168.1.10");
+        assert_eq!(fmt_ipv4(0x1_0000_0000), "0x100000000 is not an IPv4 address");
+        assert_eq!(parse_ipv6("2001:db8::1").unwrap(), 0x2001_0db8 << 96 | 1);
+        assert_eq!(parse_ipv6("::ffff:10.0.0.1").unwrap(), 0xffff_0a00_0001);
+        assert!(parse_ipv6("1::2::3").is_err());
+        assert_eq!(fmt_ipv6(1), "::1");
+    }
+
+    #[test]
+    fn test_prefixes() {
+        assert_eq!(netmask(24).unwrap(), 0xffff_ff00);
+        assert_eq!(netmask(0).unwrap(), 0);
+        assert_eq!(netmask(32).unwrap(), u32::MAX);
+        assert!(netmask(33).is_err());
+        assert_eq!(netmask6(64).unwrap(), (u64::MAX as u128) << 64);
+        assert!(netmask6(129).is_err());
+        assert_eq!(hosts(24).unwrap(), 254);
+        assert_eq!(hosts(31).unwrap(), 2);
+        assert_eq!(hosts(32).unwrap(), 1);
+        assert_eq!(hosts(0).unwrap(), (1 << 32) - 2);
+    }
+}
@@ -10,11 +10,21 @@ help_template = "\
 USAGE:
     {usage}
 
+ARGS:
+{positionals}
+
 OPTIONS:
 {options}
 ")]
 #[clap(group(ArgGroup::new("base").args(&["all", "hex", "oct", "dec", "bin"])))]
 pub struct Options {
+    #[clap(
+        value_name = "-",
+        value_parser = ["-"],
+        help = "read expressions from stdin to run line by line"
+    )]
+    pub stdin: Option<String>,
+
     #[clap(
         short,
         long,
@@ -30,6 +40,26 @@ pub struct Options {
     #[clap(short, long, help = "punctuate the output number")]
     pub punctuate_output: bool,
 
+    #[clap(long, help = "print digits from A on in upper case")]
+    pub uppercase: bool,
+
+    #[clap(long, help = "pad the output number with zeros to the width")]
+    pub zero_pad: bool,
+
+    #[clap(
+        long,
+        value_name = "STYLE",
+        value_parser = ["0x", "none", "h", "$", "c", "rust", "python"],
+        help = "mark the radix of the output number with <STYLE>"
+    )]
+    pub prefix: Option<String>,
+
+    #[clap(long, value_name = "CHAR", help = "separate digit groups with <CHAR>")]
+    pub separator: Option<char>,
+
+    #[clap(long, value_name = "N", help = "put <N> digits in a group, 0 for no groups")]
+    pub group_size: Option<usize>,
+
     #[clap(
         short,
         long,
@@ -61,6 +91,22 @@ pub struct Options {
     #[clap(short, long, help = "print in bin (only in expr eval mode)")]
     pub bin: bool,
 
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["text", "json", "tsv"],
+        default_value = "text",
+        help = "print results as text, or as JSON or TSV for scripts (only in expr eval and script mode)"
+    )]
+    pub format: String,
+
+    #[clap(
+        long,
+        conflicts_with = "format",
+        help = "rewrite prefixed numbers and $(EXPR) in the input text with their values in the output base"
+    )]
+    pub filter: bool,
+
     #[clap(short = 's', long, help = "generate history file")]
     pub history: bool,
 
@@ -0,0 +1,251 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{
+    bits::{range_mask, to_signed},
+    error::CorkError,
+    format::{align_columns, OutputFormat},
+};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// A PagingScheme is a way of translating virtual addresses through
+/// page tables. Every scheme splits an address into one index per table
+/// level followed by the page offset.
+#[derive(EnumIter, Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum PagingScheme {
+    #[default]
+    X86FourLevel,
+    X86FiveLevel,
+    Arm64Granule4K,
+    Arm64Granule16K,
+    Arm64Granule64K,
+    Sv39,
+    Sv48,
+    Sv57,
+}
+
+impl PagingScheme {
+    /// name is the keyword used in `va`, `mkva` and `set paging`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PagingScheme::X86FourLevel => "x86_4level",
+            PagingScheme::X86FiveLevel => "x86_5level",
+            PagingScheme::Arm64Granule4K => "arm64_4k",
+            PagingScheme::Arm64Granule16K => "arm64_16k",
+            PagingScheme::Arm64Granule64K => "arm64_64k",
+            PagingScheme::Sv39 => "sv39",
+            PagingScheme::Sv48 => "sv48",
+            PagingScheme::Sv57 => "sv57",
+        }
+    }
+
+    /// levels lists the table levels from the root down, with the width of
+    /// their index, and the number of offset bits below them.
+    fn levels(&self) -> (&'static [(&'static str, u32)], u32) {
+        match self {
+            PagingScheme::X86FourLevel => (&[("PML4", 9), ("PDPT", 9), ("PD", 9), ("PT", 9)], 12),
+            PagingScheme::X86FiveLevel => {
+                (&[("PML5", 9), ("PML4", 9), ("PDPT", 9), ("PD", 9), ("PT", 9)], 12)
+            }
+            PagingScheme::Arm64Granule4K => (&[("L0", 9), ("L1", 9), ("L2", 9), ("L3", 9)], 12),
+            PagingScheme::Arm64Granule16K => (&[("L0", 1), ("L1", 11), ("L2", 11), ("L3", 11)], 14),
+            PagingScheme::Arm64Granule64K => (&[("L1", 6), ("L2", 13), ("L3", 13)], 16),
+            PagingScheme::Sv39 => (&[("VPN2", 9), ("VPN1", 9), ("VPN0", 9)], 12),
+            PagingScheme::Sv48 => (&[("VPN3", 9), ("VPN2", 9), ("VPN1", 9), ("VPN0", 9)], 12),
+            PagingScheme::Sv57 => {
+                (&[("VPN4", 9), ("VPN3", 9), ("VPN2", 9), ("VPN1", 9), ("VPN0", 9)], 12)
+            }
+        }
+    }
+
+    /// va_bits is the number of address bits that go through the tables.
+    fn va_bits(&self) -> u32 {
+        let (levels, offset_bits) = self.levels();
+        levels.iter().map(|(_, bits)| bits).sum::<u32>() + offset_bits
+    }
+
+    /// canonical tells whether the bits above `va_bits` are valid. On x86-64
+    /// and RISC-V they copy the top translated bit. On ARM64 they select the
+    /// translation table instead (all zeros for TTBR0, all ones for TTBR1).
+    fn canonical(&self, addr: u64) -> bool {
+        match self {
+            PagingScheme::Arm64Granule4K
+            | PagingScheme::Arm64Granule16K
+            | PagingScheme::Arm64Granule64K => {
+                let upper = addr >> self.va_bits();
+                upper == 0 || upper == u64::MAX >> self.va_bits()
+            }
+            _ => to_signed(addr, self.va_bits()) as u64 == addr,
+        }
+    }
+
+    /// split breaks `addr` into its table indices and page offset.
+    pub fn split(&self, addr: i64) -> VaParts {
+        let addr = addr as u64;
+        let (levels, offset_bits) = self.levels();
+        let mut shift = self.va_bits();
+        let indices = levels
+            .iter()
+            .map(|(name, bits)| {
+                shift -= bits;
+                VaIndex {
+                    level: name.to_string(),
+                    index: (addr >> shift) & range_mask(0, bits - 1),
+                }
+            })
+            .collect();
+        VaParts {
+            scheme: *self,
+            addr: addr as i64,
+            indices,
+            offset: addr & range_mask(0, offset_bits - 1),
+            canonical: self.canonical(addr),
+        }
+    }
+
+    /// build makes an address from `(level, index)` pairs, where `offset`
+    /// names the page offset. Levels that are not given are zero. x86-64 and
+    /// RISC-V addresses are made canonical, ARM64 ones are left in TTBR0.
+    pub fn build(&self, values: &[(String, i64)]) -> Result<i64, CorkError> {
+        let (levels, offset_bits) = self.levels();
+        let mut addr = 0u64;
+        for (name, value) in values {
+            let (shift, bits) = if name.eq_ignore_ascii_case("offset") {
+                (0, offset_bits)
+            } else {
+                let idx = levels
+                    .iter()
+                    .position(|(level, _)| level.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| CorkError::UnknownField {
+                        layout: self.name().to_string(),
+                        field: name.clone(),
+                    })?;
+                let shift = offset_bits + levels[idx + 1..].iter().map(|(_, bits)| bits).sum::<u32>();
+                (shift, levels[idx].1)
+            };
+            if *value as u64 & !range_mask(0, bits - 1) != 0 {
+                return Err(CorkError::FieldOverflow {
+                    field: name.clone(),
+                    value: *value,
+                    bits,
+                });
+            }
+            addr |= (*value as u64) << shift;
+        }
+        if self.canonical(addr) {
+            Ok(addr as i64)
+        } else {
+            Ok(to_signed(addr, self.va_bits()))
+        }
+    }
+}
+
+impl Display for PagingScheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for PagingScheme {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PagingScheme::iter()
+            .find(|scheme| scheme.name() == s)
+            .ok_or_else(|| CorkError::InvalidValueForKey {
+                value: s.to_string(),
+                key: "paging".to_string(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VaIndex {
+    pub level: String,
+    pub index: u64,
+}
+
+/// VaParts is a virtual address split along the levels of a paging scheme.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VaParts {
+    pub scheme: PagingScheme,
+    #[serde(skip)]
+    pub addr: i64,
+    pub indices: Vec<VaIndex>,
+    pub offset: u64,
+    pub canonical: bool,
+}
+
+impl VaParts {
+    /// render lays the indices out as a table, numbers formatted with `of`.
+    pub fn render(&self, of: &OutputFormat) -> String {
+        let mut rows = vec![vec!["Level".to_string(), "Index".to_string()]];
+        rows.extend(
+            self.indices
+                .iter()
+                .map(|idx| vec![idx.level.clone(), of.fmt_unsigned(idx.index)]),
+        );
+        rows.push(vec!["Offset".to_string(), of.fmt_unsigned(self.offset)]);
+        let mut table = align_columns(&rows);
+        if !self.canonical {
+            table.push_str(&format!("\n(not canonical for {})", self.scheme));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_va_split_and_build() {
+        let addr = 0xffff_8000_1234_5678u64 as i64;
+        let parts = PagingScheme::X86FourLevel.split(addr);
+        let indices: Vec<_> = parts.indices.iter().map(|idx| idx.index).collect();
+        assert_eq!(indices, [0x100, 0x0, 0x91, 0x145]);
+        assert_eq!(parts.offset, 0x678);
+        assert!(parts.canonical);
+
+        let values: Vec<(String, i64)> = parts
+            .indices
+            .iter()
+            .map(|idx| (idx.level.clone(), idx.index as i64))
+            .chain(std::iter::once(("offset".to_string(), parts.offset as i64)))
+            .collect();
+        assert_eq!(PagingScheme::X86FourLevel.build(&values), Ok(addr));
+
+        // canonical with 5-level paging only
+        assert!(!PagingScheme::X86FourLevel.split(0x0000_8000_1234_5678).canonical);
+        assert!(PagingScheme::X86FiveLevel.split(0x0000_8000_1234_5678).canonical);
+        assert!(!PagingScheme::Sv39.split(0x0000_8000_0000_0000).canonical);
+    }
+
+    #[test]
+    fn test_va_schemes() {
+        for scheme in PagingScheme::iter() {
+            assert_eq!(scheme.name().parse::<PagingScheme>(), Ok(scheme));
+        }
+        assert_eq!(PagingScheme::X86FourLevel.va_bits(), 48);
+        assert_eq!(PagingScheme::X86FiveLevel.va_bits(), 57);
+        assert_eq!(PagingScheme::Arm64Granule16K.va_bits(), 48);
+        assert_eq!(PagingScheme::Arm64Granule64K.va_bits(), 48);
+        assert_eq!(PagingScheme::Sv39.va_bits(), 39);
+
+        let parts = PagingScheme::Arm64Granule64K.split(0x0000_ffff_ffff_0000);
+        let indices: Vec<_> = parts.indices.iter().map(|idx| idx.index).collect();
+        assert_eq!(indices, [0x3f, 0x1fff, 0x1fff]);
+        assert!(parts.canonical);
+        assert_eq!(
+            PagingScheme::Arm64Granule4K.build(&[("L0".to_string(), 0x1ff)]),
+            Ok(0x0000_ff80_0000_0000)
+        );
+        assert_eq!(
+            PagingScheme::Sv39.build(&[("vpn2".to_string(), 0x200)]),
+            Err(CorkError::FieldOverflow { field: "vpn2".to_string(), value: 0x200, bits: 9 })
+        );
+    }
+}
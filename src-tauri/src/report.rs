@@ -0,0 +1,193 @@
+use std::str::FromStr;
+
+use crate::{
+    error::CorkError,
+    expression::PestRuleError,
+    format::{FormatRadix, OutputFormat},
+};
+use pest::error::InputLocation;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use strum::IntoEnumIterator;
+
+/// ReportFormat is how results are written for scripts and editor plugins
+/// to read, instead of the text meant for people.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A JSON object per line.
+    Json,
+    /// A row of tab separated values per line, after a header row.
+    Tsv,
+}
+
+impl FromStr for ReportFormat {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ReportFormat::Json),
+            "tsv" => Ok(ReportFormat::Tsv),
+            _ => Err(CorkError::InvalidValueForKey {
+                value: s.to_string(),
+                key: "format".to_string(),
+            }),
+        }
+    }
+}
+
+/// A ReportValue is a number, or the text of a value that is not one.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ReportValue {
+    Int(i64),
+    Text(String),
+}
+
+/// Renderings are a value in every output format, named as after `to`. They
+/// are serialized as an object that keeps the order of the formats.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Renderings(Vec<(String, String)>);
+
+impl Serialize for Renderings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, rendering) in &self.0 {
+            map.serialize_entry(name, rendering)?;
+        }
+        map.end()
+    }
+}
+
+/// A Report is the result of evaluating one line. The span is the byte range
+/// of the line that was evaluated, or the position a parse error points at.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Report {
+    input: String,
+    value: Option<ReportValue>,
+    renderings: Renderings,
+    width: u32,
+    error: Option<String>,
+    span: (usize, usize),
+}
+
+impl Report {
+    pub fn new(input: &str, width: u32) -> Report {
+        let start = input.len() - input.trim_start().len();
+        Report {
+            input: input.to_string(),
+            value: None,
+            renderings: Renderings::default(),
+            width,
+            error: None,
+            span: (start, input.trim_end().len().max(start)),
+        }
+    }
+
+    /// with_value renders `value` in every output format, in the style of
+    /// `of`.
+    pub fn with_value(mut self, value: i64, of: &OutputFormat) -> Report {
+        self.value = Some(ReportValue::Int(value));
+        self.renderings = Renderings(
+            FormatRadix::iter()
+                .map(|radix| (radix.name(), of.with_format_radix(radix).fmt(value)))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_text(mut self, text: String) -> Report {
+        self.value = Some(ReportValue::Text(text));
+        self
+    }
+
+    /// with_error keeps the message of `err`. Parse errors keep what was
+    /// expected, without the drawing of the line, and where it was found.
+    pub fn with_error(mut self, err: &anyhow::Error) -> Report {
+        self.error = Some(err.to_string());
+        let parse_error = err.downcast_ref::<PestRuleError>().or_else(|| match err.downcast_ref() {
+            Some(CorkError::Parse(parse_error)) => Some(parse_error.as_ref()),
+            _ => None,
+        });
+        if let Some(parse_error) = parse_error {
+            self.error = Some(parse_error.variant.message().to_string());
+            self.span = match parse_error.location {
+                InputLocation::Pos(pos) => (pos, pos),
+                InputLocation::Span(span) => span,
+            };
+        }
+        self
+    }
+
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// write gives the report as a line of `format`, without the newline.
+    pub fn write(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => serde_json::to_string(self).expect("reports serialize to JSON"),
+            ReportFormat::Tsv => {
+                let value = match &self.value {
+                    Some(ReportValue::Int(value)) => value.to_string(),
+                    Some(ReportValue::Text(text)) => text.clone(),
+                    None => String::new(),
+                };
+                let mut fields = vec![
+                    self.input.clone(),
+                    value,
+                    self.width.to_string(),
+                    self.error.clone().unwrap_or_default(),
+                    self.span.0.to_string(),
+                    self.span.1.to_string(),
+                ];
+                // values that are not numbers have no renderings, their columns stay empty
+                let renderings = self.renderings.0.iter().map(|(_, rendering)| rendering.clone());
+                fields.extend(renderings.chain(std::iter::repeat(String::new())).take(FormatRadix::iter().count()));
+                fields.iter().map(|field| tsv_escape(field)).collect::<Vec<_>>().join("\t")
+            }
+        }
+    }
+}
+
+/// tsv_header names the columns of the TSV reports.
+pub fn tsv_header() -> String {
+    let columns = ["input", "value", "width", "error", "start", "end"].map(String::from);
+    columns.into_iter().chain(FormatRadix::iter().map(|radix| radix.name())).collect::<Vec<_>>().join("\t")
+}
+
+/// tsv_escape keeps a field on its line and in its column.
+fn tsv_escape(field: &str) -> String {
+    field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{config::Config, expression};
+
+    #[test]
+    fn test_report_json() {
+        let of = OutputFormat::default();
+        let report = Report::new(" 0x10 ", 64).with_value(16, &of);
+        let json: serde_json::Value = serde_json::from_str(&report.write(ReportFormat::Json)).unwrap();
+        assert_eq!(json["input"], " 0x10 ");
+        assert_eq!(json["value"], 16);
+        assert_eq!(json["renderings"]["hex"], "0x10");
+        assert_eq!(json["renderings"]["dec"], "0d16");
+        assert_eq!(json["span"], serde_json::json!([1, 5]));
+        assert_eq!(json["error"], serde_json::Value::Null);
+
+        let err = expression::parse_line("1 +", &Config::new()).unwrap_err();
+        let report = Report::new("1 +", 64).with_error(&err);
+        assert!(report.failed());
+        assert!(report.error.as_ref().unwrap().starts_with("expected "));
+        assert_eq!(report.span, (3, 3));
+    }
+
+    #[test]
+    fn test_report_tsv() {
+        let report = Report::new("\"a\tb\"", 32).with_text("a\tb".to_string());
+        let row = report.write(ReportFormat::Tsv);
+        assert_eq!(row.split('\t').count(), tsv_header().split('\t').count());
+        assert!(row.starts_with("\"a\\tb\"\ta\\tb\t32\t\t0\t5\t"));
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    bits::{self, BitEdit},
+    config::{history_location, layouts_location, wordlist_location, Config},
+    error::CorkError,
+    expression::eval::EvalContext,
+    format::{Cyclic, Endian},
+    hashes::{HashAlgo, Wordlist},
+    history::History,
+    layout::Layouts,
+    modules::Modules,
+    paging::PagingScheme,
+};
+use anyhow::{Context, Result as AResult};
+
+/// A Session is the state shared by consecutive evaluations: the last
+/// answer, the width and byte order it is viewed in, the evaluation history,
+/// the register layouts, the paging scheme addresses are split with, the
+/// module bases defined so far, the cyclic pattern settings and the
+/// wordlist hashes are looked up in.
+#[derive(Debug)]
+pub struct Session {
+    pub ans: i64,
+    pub width: u32,
+    pub endian: Endian,
+    pub history: History,
+    pub layouts: Layouts,
+    pub paging: PagingScheme,
+    pub modules: Modules,
+    pub cyclic: Cyclic,
+    keep_history: bool,
+    history_path: Option<PathBuf>,
+    wordlist_path: Option<PathBuf>,
+    wordlist: Option<Wordlist>,
+}
+
+impl Session {
+    /// load restores the history and layouts kept next to the config file.
+    /// A history that can't be read is moved aside, so that saving doesn't
+    /// overwrite it, and an empty one is started; a broken layouts file is
+    /// reported.
+    pub fn load<T: AsRef<Path>>(config_path: Option<T>) -> AResult<Session> {
+        let history_path = history_location(config_path.as_ref());
+        let history = match &history_path {
+            Some(path) => load_history(path)?,
+            None => History::default(),
+        };
+        let mut layouts = Layouts::builtin();
+        if let Some(path) = layouts_location(config_path.as_ref()) {
+            layouts.extend(Layouts::load(path)?);
+        }
+        Ok(Session {
+            ans: 0,
+            width: 64,
+            endian: Endian::default(),
+            history,
+            layouts,
+            paging: PagingScheme::default(),
+            modules: Modules::default(),
+            cyclic: Cyclic::default(),
+            keep_history: false,
+            history_path,
+            wordlist_path: wordlist_location(config_path.as_ref()),
+            wordlist: None,
+        })
+    }
+
+    /// configure takes the settings the config file has for the session.
+    pub fn configure(&mut self, config: &Config) -> Result<(), CorkError> {
+        self.width = bits::check_width(*config.width())?;
+        self.endian = *config.endian();
+        self.cyclic = Cyclic::new(config.cyclic_alphabet(), *config.cyclic_n())?;
+        self.keep_history = *config.history();
+        if let Some(path) = config.wordlist() {
+            self.wordlist_path = Some(path.clone());
+            self.wordlist = None;
+        }
+        Ok(())
+    }
+
+    /// save writes the history when the config asks to keep it.
+    pub fn save(&self) -> AResult<()> {
+        if !self.keep_history {
+            return Ok(());
+        }
+        if let Some(path) = &self.history_path {
+            self.history.save(path)?;
+        }
+        Ok(())
+    }
+
+    /// edit_bits applies `edit` to the current value in the current width.
+    pub fn edit_bits(&mut self, edit: BitEdit) -> Result<i64, CorkError> {
+        self.ans = edit.apply(self.ans, self.width)?;
+        Ok(self.ans)
+    }
+
+    /// hash_lookup gives the wordlist names whose `algo` hash is `value`,
+    /// and the number of names searched. The wordlist is read on first use.
+    pub fn hash_lookup(&mut self, algo: HashAlgo, value: i64) -> AResult<(Vec<String>, usize)> {
+        if self.wordlist.is_none() {
+            let path = self.wordlist_path.as_ref().context("no wordlist location")?;
+            self.wordlist = Some(Wordlist::load(path)?);
+        }
+        let wordlist = self.wordlist.as_mut().expect("wordlist was just loaded");
+        let found = wordlist.lookup(algo, value).into_iter().map(String::from).collect();
+        Ok((found, wordlist.len()))
+    }
+
+    pub fn context(&self) -> EvalContext<'_> {
+        EvalContext::new(self.ans)
+            .with_history(&self.history)
+            .with_modules(&self.modules)
+            .with_bytes(self.width, self.endian)
+            .with_cyclic(&self.cyclic)
+    }
+}
+
+/// load_history reads the history at `path`. When it can't be read, it is
+/// renamed with a `.bad` extension and an empty history is started.
+fn load_history(path: &Path) -> AResult<History> {
+    match History::load(path) {
+        Ok(history) => Ok(history),
+        Err(err) => {
+            let aside = path.with_extension("json.bad");
+            fs::rename(path, &aside)
+                .with_context(|| format!("failed to move the unreadable history {} aside", path.display()))?;
+            eprintln!("Failed to read evaluation history: {}, moved it to {}", err, aside.display());
+            Ok(History::default())
+        }
+    }
+}
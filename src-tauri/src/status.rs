@@ -0,0 +1,432 @@
+use serde::Serialize;
+
+/// A Meaning is what a value stands for as a status code of one kind.
+/// `detail` breaks NTSTATUS and HRESULT values up into their fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Meaning {
+    pub kind: &'static str,
+    pub name: String,
+    pub description: String,
+    pub detail: Option<String>,
+}
+
+/// Linux errno values, as in asm-generic/errno-base.h and errno.h.
+static ERRNO: &[(u32, &str, &str)] = &[
+    (1, "EPERM", "Operation not permitted"),
+    (2, "ENOENT", "No such file or directory"),
+    (3, "ESRCH", "No such process"),
+    (4, "EINTR", "Interrupted system call"),
+    (5, "EIO", "Input/output error"),
+    (6, "ENXIO", "No such device or address"),
+    (7, "E2BIG", "Argument list too long"),
+    (8, "ENOEXEC", "Exec format error"),
+    (9, "EBADF", "Bad file descriptor"),
+    (10, "ECHILD", "No child processes"),
+    (11, "EAGAIN", "Resource temporarily unavailable"),
+    (12, "ENOMEM", "Cannot allocate memory"),
+    (13, "EACCES", "Permission denied"),
+    (14, "EFAULT", "Bad address"),
+    (15, "ENOTBLK", "Block device required"),
+    (16, "EBUSY", "Device or resource busy"),
+    (17, "EEXIST", "File exists"),
+    (18, "EXDEV", "Invalid cross-device link"),
+    (19, "ENODEV", "No such device"),
+    (20, "ENOTDIR", "Not a directory"),
+    (21, "EISDIR", "Is a directory"),
+    (22, "EINVAL", "Invalid argument"),
+    (23, "ENFILE", "Too many open files in system"),
+    (24, "EMFILE", "Too many open files"),
+    (25, "ENOTTY", "Inappropriate ioctl for device"),
+    (26, "ETXTBSY", "Text file busy"),
+    (27, "EFBIG", "File too large"),
+    (28, "ENOSPC", "No space left on device"),
+    (29, "ESPIPE", "Illegal seek"),
+    (30, "EROFS", "Read-only file system"),
+    (31, "EMLINK", "Too many links"),
+    (32, "EPIPE", "Broken pipe"),
+    (33, "EDOM", "Numerical argument out of domain"),
+    (34, "ERANGE", "Numerical result out of range"),
+    (35, "EDEADLK", "Resource deadlock avoided"),
+    (36, "ENAMETOOLONG", "File name too long"),
+    (37, "ENOLCK", "No locks available"),
+    (38, "ENOSYS", "Function not implemented"),
+    (39, "ENOTEMPTY", "Directory not empty"),
+    (40, "ELOOP", "Too many levels of symbolic links"),
+    (42, "ENOMSG", "No message of desired type"),
+    (43, "EIDRM", "Identifier removed"),
+    (44, "ECHRNG", "Channel number out of range"),
+    (45, "EL2NSYNC", "Level 2 not synchronized"),
+    (46, "EL3HLT", "Level 3 halted"),
+    (47, "EL3RST", "Level 3 reset"),
+    (48, "ELNRNG", "Link number out of range"),
+    (49, "EUNATCH", "Protocol driver not attached"),
+    (50, "ENOCSI", "No CSI structure available"),
+    (51, "EL2HLT", "Level 2 halted"),
+    (52, "EBADE", "Invalid exchange"),
+    (53, "EBADR", "Invalid request descriptor"),
+    (54, "EXFULL", "Exchange full"),
+    (55, "ENOANO", "No anode"),
+    (56, "EBADRQC", "Invalid request code"),
+    (57, "EBADSLT", "Invalid slot"),
+    (59, "EBFONT", "Bad font file format"),
+    (60, "ENOSTR", "Device not a stream"),
+    (61, "ENODATA", "No data available"),
+    (62, "ETIME", "Timer expired"),
+    (63, "ENOSR", "Out of streams resources"),
+    (64, "ENONET", "Machine is not on the network"),
+    (65, "ENOPKG", "Package not installed"),
+    (66, "EREMOTE", "Object is remote"),
+    (67, "ENOLINK", "Link has been severed"),
+    (68, "EADV", "Advertise error"),
+    (69, "ESRMNT", "Srmount error"),
+    (70, "ECOMM", "Communication error on send"),
+    (71, "EPROTO", "Protocol error"),
+    (72, "EMULTIHOP", "Multihop attempted"),
+    (73, "EDOTDOT", "RFS specific error"),
+    (74, "EBADMSG", "Bad message"),
+    (75, "EOVERFLOW", "Value too large for defined data type"),
+    (76, "ENOTUNIQ", "Name not unique on network"),
+    (77, "EBADFD", "File descriptor in bad state"),
+    (78, "EREMCHG", "Remote address changed"),
+    (79, "ELIBACC", "Can not access a needed shared library"),
+    (80, "ELIBBAD", "Accessing a corrupted shared library"),
+    (81, "ELIBSCN", ".lib section in a.out corrupted"),
+    (82, "ELIBMAX", "Attempting to link in too many shared libraries"),
+    (83, "ELIBEXEC", "Cannot exec a shared library directly"),
+    (84, "EILSEQ", "Invalid or incomplete multibyte or wide character"),
+    (85, "ERESTART", "Interrupted system call should be restarted"),
+    (86, "ESTRPIPE", "Streams pipe error"),
+    (87, "EUSERS", "Too many users"),
+    (88, "ENOTSOCK", "Socket operation on non-socket"),
+    (89, "EDESTADDRREQ", "Destination address required"),
+    (90, "EMSGSIZE", "Message too long"),
+    (91, "EPROTOTYPE", "Protocol wrong type for socket"),
+    (92, "ENOPROTOOPT", "Protocol not available"),
+    (93, "EPROTONOSUPPORT", "Protocol not supported"),
+    (94, "ESOCKTNOSUPPORT", "Socket type not supported"),
+    (95, "EOPNOTSUPP", "Operation not supported"),
+    (96, "EPFNOSUPPORT", "Protocol family not supported"),
+    (97, "EAFNOSUPPORT", "Address family not supported by protocol"),
+    (98, "EADDRINUSE", "Address already in use"),
+    (99, "EADDRNOTAVAIL", "Cannot assign requested address"),
+    (100, "ENETDOWN", "Network is down"),
+    (101, "ENETUNREACH", "Network is unreachable"),
+    (102, "ENETRESET", "Network dropped connection on reset"),
+    (103, "ECONNABORTED", "Software caused connection abort"),
+    (104, "ECONNRESET", "Connection reset by peer"),
+    (105, "ENOBUFS", "No buffer space available"),
+    (106, "EISCONN", "Transport endpoint is already connected"),
+    (107, "ENOTCONN", "Transport endpoint is not connected"),
+    (108, "ESHUTDOWN", "Cannot send after transport endpoint shutdown"),
+    (109, "ETOOMANYREFS", "Too many references: cannot splice"),
+    (110, "ETIMEDOUT", "Connection timed out"),
+    (111, "ECONNREFUSED", "Connection refused"),
+    (112, "EHOSTDOWN", "Host is down"),
+    (113, "EHOSTUNREACH", "No route to host"),
+    (114, "EALREADY", "Operation already in progress"),
+    (115, "EINPROGRESS", "Operation now in progress"),
+    (116, "ESTALE", "Stale file handle"),
+    (117, "EUCLEAN", "Structure needs cleaning"),
+    (118, "ENOTNAM", "Not a XENIX named type file"),
+    (119, "ENAVAIL", "No XENIX semaphores available"),
+    (120, "EISNAM", "Is a named type file"),
+    (121, "EREMOTEIO", "Remote I/O error"),
+    (122, "EDQUOT", "Disk quota exceeded"),
+    (123, "ENOMEDIUM", "No medium found"),
+    (124, "EMEDIUMTYPE", "Wrong medium type"),
+    (125, "ECANCELED", "Operation canceled"),
+    (126, "ENOKEY", "Required key not available"),
+    (127, "EKEYEXPIRED", "Key has expired"),
+    (128, "EKEYREVOKED", "Key has been revoked"),
+    (129, "EKEYREJECTED", "Key was rejected by service"),
+    (130, "EOWNERDEAD", "Owner died"),
+    (131, "ENOTRECOVERABLE", "State not recoverable"),
+    (132, "ERFKILL", "Operation not possible due to RF-kill"),
+    (133, "EHWPOISON", "Memory page has hardware error"),
+];
+
+/// Common NTSTATUS values, from ntstatus.h.
+static NTSTATUS: &[(u32, &str, &str)] = &[
+    (0x0000_0000, "STATUS_SUCCESS", "The operation completed successfully"),
+    (0x0000_0080, "STATUS_ABANDONED", "The wait was satisfied by an abandoned mutex"),
+    (0x0000_00c0, "STATUS_USER_APC", "A user APC was delivered during the wait"),
+    (0x0000_0101, "STATUS_ALERTED", "The wait was alerted"),
+    (0x0000_0102, "STATUS_TIMEOUT", "The wait timed out"),
+    (0x0000_0103, "STATUS_PENDING", "The operation has not completed yet"),
+    (0x0000_0104, "STATUS_REPARSE", "The name has to be reparsed"),
+    (0x0000_0105, "STATUS_MORE_ENTRIES", "More entries are available"),
+    (0x4000_0015, "STATUS_FATAL_APP_EXIT", "The application exited with a fatal error"),
+    (0x8000_0001, "STATUS_GUARD_PAGE_VIOLATION", "A guard page was accessed"),
+    (0x8000_0002, "STATUS_DATATYPE_MISALIGNMENT", "A misaligned data access"),
+    (0x8000_0003, "STATUS_BREAKPOINT", "A breakpoint was hit"),
+    (0x8000_0004, "STATUS_SINGLE_STEP", "A single step or trace completed"),
+    (0x8000_0005, "STATUS_BUFFER_OVERFLOW", "The data did not fit in the buffer"),
+    (0x8000_0006, "STATUS_NO_MORE_FILES", "No more files match the request"),
+    (0xc000_0001, "STATUS_UNSUCCESSFUL", "The operation failed"),
+    (0xc000_0002, "STATUS_NOT_IMPLEMENTED", "The function is not implemented"),
+    (0xc000_0003, "STATUS_INVALID_INFO_CLASS", "The information class is not valid"),
+    (0xc000_0004, "STATUS_INFO_LENGTH_MISMATCH", "The length does not match the information class"),
+    (0xc000_0005, "STATUS_ACCESS_VIOLATION", "The memory could not be accessed"),
+    (0xc000_0006, "STATUS_IN_PAGE_ERROR", "A page could not be read in"),
+    (0xc000_0008, "STATUS_INVALID_HANDLE", "The handle is not valid"),
+    (0xc000_000d, "STATUS_INVALID_PARAMETER", "A parameter is not valid"),
+    (0xc000_000f, "STATUS_NO_SUCH_FILE", "The file does not exist"),
+    (0xc000_0010, "STATUS_INVALID_DEVICE_REQUEST", "The request is not valid for the device"),
+    (0xc000_0011, "STATUS_END_OF_FILE", "The end of the file was reached"),
+    (0xc000_0017, "STATUS_NO_MEMORY", "Not enough virtual memory or paging file quota"),
+    (0xc000_0018, "STATUS_CONFLICTING_ADDRESSES", "The address range is already in use"),
+    (0xc000_001c, "STATUS_INVALID_SYSTEM_SERVICE", "The system service number is not valid"),
+    (0xc000_001d, "STATUS_ILLEGAL_INSTRUCTION", "An invalid instruction was executed"),
+    (0xc000_0022, "STATUS_ACCESS_DENIED", "Access was denied"),
+    (0xc000_0023, "STATUS_BUFFER_TOO_SMALL", "The buffer is too small"),
+    (0xc000_0024, "STATUS_OBJECT_TYPE_MISMATCH", "The object is of the wrong type"),
+    (0xc000_0025, "STATUS_NONCONTINUABLE_EXCEPTION", "Execution cannot continue after the exception"),
+    (0xc000_0033, "STATUS_OBJECT_NAME_INVALID", "The object name is not valid"),
+    (0xc000_0034, "STATUS_OBJECT_NAME_NOT_FOUND", "The object name was not found"),
+    (0xc000_0035, "STATUS_OBJECT_NAME_COLLISION", "The object name already exists"),
+    (0xc000_003a, "STATUS_OBJECT_PATH_NOT_FOUND", "The path does not exist"),
+    (0xc000_0043, "STATUS_SHARING_VIOLATION", "The file is in use with incompatible sharing"),
+    (0xc000_0056, "STATUS_DELETE_PENDING", "The file is about to be deleted"),
+    (0xc000_0061, "STATUS_PRIVILEGE_NOT_HELD", "A required privilege is not held"),
+    (0xc000_007a, "STATUS_PROCEDURE_NOT_FOUND", "The procedure was not found in the DLL"),
+    (0xc000_007b, "STATUS_INVALID_IMAGE_FORMAT", "The image is not valid for this system"),
+    (0xc000_008c, "STATUS_ARRAY_BOUNDS_EXCEEDED", "An array index was out of bounds"),
+    (0xc000_008e, "STATUS_FLOAT_DIVIDE_BY_ZERO", "A floating-point division by zero"),
+    (0xc000_0094, "STATUS_INTEGER_DIVIDE_BY_ZERO", "An integer division by zero"),
+    (0xc000_0095, "STATUS_INTEGER_OVERFLOW", "An integer overflow"),
+    (0xc000_0096, "STATUS_PRIVILEGED_INSTRUCTION", "A privileged instruction was executed"),
+    (0xc000_009a, "STATUS_INSUFFICIENT_RESOURCES", "Not enough system resources"),
+    (0xc000_00bb, "STATUS_NOT_SUPPORTED", "The request is not supported"),
+    (0xc000_00fd, "STATUS_STACK_OVERFLOW", "The stack overflowed"),
+    (0xc000_0120, "STATUS_CANCELLED", "The I/O request was cancelled"),
+    (0xc000_0135, "STATUS_DLL_NOT_FOUND", "A DLL the program needs was not found"),
+    (0xc000_0138, "STATUS_ORDINAL_NOT_FOUND", "The ordinal was not found in the DLL"),
+    (0xc000_0139, "STATUS_ENTRYPOINT_NOT_FOUND", "The entry point was not found in the DLL"),
+    (0xc000_013a, "STATUS_CONTROL_C_EXIT", "The application was ended by Ctrl+C"),
+    (0xc000_0142, "STATUS_DLL_INIT_FAILED", "A DLL failed to initialize"),
+    (0xc000_0225, "STATUS_NOT_FOUND", "The object was not found"),
+    (0xc000_0374, "STATUS_HEAP_CORRUPTION", "The heap is corrupted"),
+    (0xc000_0409, "STATUS_STACK_BUFFER_OVERRUN", "A stack buffer overrun, or a fail fast"),
+    (0xc000_0417, "STATUS_INVALID_CRUNTIME_PARAMETER", "A C runtime function got an invalid parameter"),
+    (0xc000_0420, "STATUS_ASSERTION_FAILURE", "An assertion failed"),
+    (0xc000_0602, "STATUS_FAIL_FAST_EXCEPTION", "A fail fast exception was raised"),
+];
+
+/// Common HRESULT values, from winerror.h. Those that wrap a Win32 error
+/// or an NTSTATUS are found through those tables.
+static HRESULT: &[(u32, &str, &str)] = &[
+    (0x0000_0000, "S_OK", "The operation succeeded"),
+    (0x0000_0001, "S_FALSE", "The operation succeeded with a false result"),
+    (0x8000_4001, "E_NOTIMPL", "Not implemented"),
+    (0x8000_4002, "E_NOINTERFACE", "No such interface supported"),
+    (0x8000_4003, "E_POINTER", "Invalid pointer"),
+    (0x8000_4004, "E_ABORT", "Operation aborted"),
+    (0x8000_4005, "E_FAIL", "Unspecified error"),
+    (0x8000_000a, "E_PENDING", "The data is not available yet"),
+    (0x8000_000b, "E_BOUNDS", "The operation was out of bounds"),
+    (0x8000_000e, "E_ILLEGAL_METHOD_CALL", "The method call is not valid in this state"),
+    (0x8000_ffff, "E_UNEXPECTED", "Catastrophic failure"),
+    (0x8001_0106, "RPC_E_CHANGED_MODE", "The thread's concurrency model cannot be changed"),
+    (0x8001_010e, "RPC_E_WRONG_THREAD", "The interface was marshalled for a different thread"),
+    (0x8002_0003, "DISP_E_MEMBERNOTFOUND", "Member not found"),
+    (0x8002_0005, "DISP_E_TYPEMISMATCH", "Type mismatch"),
+    (0x8002_0009, "DISP_E_EXCEPTION", "Exception occurred"),
+    (0x8004_0110, "CLASS_E_NOAGGREGATION", "The class does not support aggregation"),
+    (0x8004_0154, "REGDB_E_CLASSNOTREG", "Class not registered"),
+    (0x8004_01f0, "CO_E_NOTINITIALIZED", "CoInitialize has not been called"),
+];
+
+/// Common Win32 error codes, from winerror.h.
+static WIN32: &[(u32, &str, &str)] = &[
+    (0, "ERROR_SUCCESS", "The operation completed successfully"),
+    (1, "ERROR_INVALID_FUNCTION", "Incorrect function"),
+    (2, "ERROR_FILE_NOT_FOUND", "The system cannot find the file specified"),
+    (3, "ERROR_PATH_NOT_FOUND", "The system cannot find the path specified"),
+    (4, "ERROR_TOO_MANY_OPEN_FILES", "The system cannot open the file"),
+    (5, "ERROR_ACCESS_DENIED", "Access is denied"),
+    (6, "ERROR_INVALID_HANDLE", "The handle is invalid"),
+    (8, "ERROR_NOT_ENOUGH_MEMORY", "Not enough memory resources are available"),
+    (13, "ERROR_INVALID_DATA", "The data is invalid"),
+    (14, "ERROR_OUTOFMEMORY", "Not enough storage is available to complete this operation"),
+    (15, "ERROR_INVALID_DRIVE", "The system cannot find the drive specified"),
+    (18, "ERROR_NO_MORE_FILES", "There are no more files"),
+    (19, "ERROR_WRITE_PROTECT", "The media is write protected"),
+    (21, "ERROR_NOT_READY", "The device is not ready"),
+    (24, "ERROR_BAD_LENGTH", "The program issued a command but the command length is incorrect"),
+    (32, "ERROR_SHARING_VIOLATION", "The file is being used by another process"),
+    (33, "ERROR_LOCK_VIOLATION", "Another process has locked a portion of the file"),
+    (38, "ERROR_HANDLE_EOF", "Reached the end of the file"),
+    (50, "ERROR_NOT_SUPPORTED", "The request is not supported"),
+    (80, "ERROR_FILE_EXISTS", "The file exists"),
+    (87, "ERROR_INVALID_PARAMETER", "The parameter is incorrect"),
+    (109, "ERROR_BROKEN_PIPE", "The pipe has been ended"),
+    (122, "ERROR_INSUFFICIENT_BUFFER", "The data area passed to a system call is too small"),
+    (123, "ERROR_INVALID_NAME", "The filename, directory name, or volume label syntax is incorrect"),
+    (126, "ERROR_MOD_NOT_FOUND", "The specified module could not be found"),
+    (127, "ERROR_PROC_NOT_FOUND", "The specified procedure could not be found"),
+    (145, "ERROR_DIR_NOT_EMPTY", "The directory is not empty"),
+    (183, "ERROR_ALREADY_EXISTS", "Cannot create a file when that file already exists"),
+    (193, "ERROR_BAD_EXE_FORMAT", "The file is not a valid Win32 application"),
+    (203, "ERROR_ENVVAR_NOT_FOUND", "The system could not find the environment option that was entered"),
+    (234, "ERROR_MORE_DATA", "More data is available"),
+    (259, "ERROR_NO_MORE_ITEMS", "No more data is available"),
+    (267, "ERROR_DIRECTORY", "The directory name is invalid"),
+    (288, "ERROR_NOT_OWNER", "Attempt to release mutex not owned by caller"),
+    (299, "ERROR_PARTIAL_COPY", "Only part of a memory read or write request was completed"),
+    (487, "ERROR_INVALID_ADDRESS", "Attempt to access invalid address"),
+    (740, "ERROR_ELEVATION_REQUIRED", "The requested operation requires elevation"),
+    (995, "ERROR_OPERATION_ABORTED", "The I/O operation has been aborted"),
+    (997, "ERROR_IO_PENDING", "Overlapped I/O operation is in progress"),
+    (998, "ERROR_NOACCESS", "Invalid access to memory location"),
+    (1001, "ERROR_STACK_OVERFLOW", "Recursion too deep; the stack overflowed"),
+    (1004, "ERROR_INVALID_FLAGS", "Invalid flags"),
+    (1060, "ERROR_SERVICE_DOES_NOT_EXIST", "The specified service does not exist as an installed service"),
+    (1114, "ERROR_DLL_INIT_FAILED", "A dynamic link library (DLL) initialization routine failed"),
+    (1168, "ERROR_NOT_FOUND", "Element not found"),
+    (1223, "ERROR_CANCELLED", "The operation was canceled by the user"),
+    (1314, "ERROR_PRIVILEGE_NOT_HELD", "A required privilege is not held by the client"),
+    (1326, "ERROR_LOGON_FAILURE", "The user name or password is incorrect"),
+    (1400, "ERROR_INVALID_WINDOW_HANDLE", "Invalid window handle"),
+    (1455, "ERROR_COMMITMENT_LIMIT", "The paging file is too small for this operation to complete"),
+    (1460, "ERROR_TIMEOUT", "This operation returned because the timeout period expired"),
+];
+
+const HRESULT_FACILITIES: &[(u32, &str)] = &[
+    (0, "NULL"),
+    (1, "RPC"),
+    (2, "DISPATCH"),
+    (3, "STORAGE"),
+    (4, "ITF"),
+    (7, "WIN32"),
+    (8, "WINDOWS"),
+    (9, "SECURITY"),
+    (10, "CONTROL"),
+    (11, "CERT"),
+    (12, "INTERNET"),
+    (15, "SETUPAPI"),
+    (16, "SCARD"),
+    (17, "COMPLUS"),
+    (19, "URT"),
+    (25, "HTTP"),
+    (36, "WINDOWSUPDATE"),
+    (38, "GRAPHICS"),
+    (39, "SHELL"),
+];
+
+const FACILITY_WIN32: u32 = 7;
+/// The N bit marks an HRESULT made from an NTSTATUS.
+const FACILITY_NT_BIT: u32 = 0x1000_0000;
+
+fn find(table: &'static [(u32, &'static str, &'static str)], code: u32) -> Option<(&'static str, &'static str)> {
+    table
+        .iter()
+        .find(|(value, _, _)| *value == code)
+        .map(|(_, name, description)| (*name, *description))
+}
+
+fn ntstatus_detail(code: u32) -> String {
+    let severity = ["success", "informational", "warning", "error"][(code >> 30) as usize];
+    let customer = if code & 0x2000_0000 != 0 { ", customer" } else { "" };
+    format!(
+        "{}{}, facility {:#x}, code {:#x}",
+        severity,
+        customer,
+        (code >> 16) & 0xfff,
+        code & 0xffff
+    )
+}
+
+fn hresult_detail(code: u32) -> String {
+    let result = if code & 0x8000_0000 != 0 { "failure" } else { "success" };
+    let facility = (code >> 16) & 0x7ff;
+    let facility = match HRESULT_FACILITIES.iter().find(|(value, _)| *value == facility) {
+        Some((_, name)) => format!("{} ({:#x})", name, facility),
+        None => format!("{:#x}", facility),
+    };
+    format!("{}, facility {}, code {:#x}", result, facility, code & 0xffff)
+}
+
+/// whatis lists what `value` means as a Linux errno, positive or negated as
+/// the kernel returns it, as an NTSTATUS, an HRESULT or a Win32 error code.
+/// 32 bit codes may be given sign extended.
+pub fn whatis(value: i64) -> Vec<Meaning> {
+    let mut meanings = Vec::new();
+    let code = match (u32::try_from(value), i32::try_from(value)) {
+        (Ok(code), _) => code,
+        (_, Ok(code)) => code as u32,
+        _ => return meanings,
+    };
+    let meaning = |kind, name: String, description: &str, detail: Option<String>| Meaning {
+        kind,
+        name,
+        description: description.to_string(),
+        detail,
+    };
+
+    let errno = code as i32;
+    if let Some((name, description)) = find(ERRNO, errno.unsigned_abs()) {
+        let name = if errno < 0 { format!("-{}", name) } else { name.to_string() };
+        meanings.push(meaning("errno", name, description, None));
+    }
+    if let Some((name, description)) = find(NTSTATUS, code) {
+        meanings.push(meaning("NTSTATUS", name.to_string(), description, Some(ntstatus_detail(code))));
+    }
+    let hresult = if let Some((name, description)) = find(HRESULT, code) {
+        Some((name.to_string(), description))
+    } else if code & 0xffff_0000 == 0x8000_0000 | FACILITY_WIN32 << 16 {
+        find(WIN32, code & 0xffff).map(|(name, description)| (format!("HRESULT_FROM_WIN32({})", name), description))
+    } else if code & FACILITY_NT_BIT != 0 {
+        find(NTSTATUS, code & !FACILITY_NT_BIT)
+            .map(|(name, description)| (format!("HRESULT_FROM_NT({})", name), description))
+    } else {
+        None
+    };
+    if let Some((name, description)) = hresult {
+        meanings.push(meaning("HRESULT", name, description, Some(hresult_detail(code))));
+    }
+    if let Some((name, description)) = find(WIN32, code) {
+        meanings.push(meaning("Win32", name.to_string(), description, None));
+    }
+
+    // an unknown failure code still tells something by its fields
+    if meanings.is_empty() && code & 0x8000_0000 != 0 {
+        meanings.push(meaning("NTSTATUS", "?".to_string(), "", Some(ntstatus_detail(code))));
+        if code & 0x4000_0000 == 0 {
+            meanings.push(meaning("HRESULT", "?".to_string(), "", Some(hresult_detail(code))));
+        }
+    }
+    meanings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(value: i64) -> Vec<(&'static str, String)> {
+        whatis(value).into_iter().map(|meaning| (meaning.kind, meaning.name)).collect()
+    }
+
+    #[test]
+    fn test_whatis() {
+        assert_eq!(
+            names(2),
+            [("errno", "ENOENT".to_string()), ("Win32", "ERROR_FILE_NOT_FOUND".to_string())]
+        );
+        assert_eq!(names(-13), [("errno", "-EACCES".to_string())]);
+        assert_eq!(names(0xffff_fff2), [("errno", "-EFAULT".to_string())]);
+        assert_eq!(names(0xc000_0005), [("NTSTATUS", "STATUS_ACCESS_VIOLATION".to_string())]);
+        assert_eq!(names(-1_073_741_819), [("NTSTATUS", "STATUS_ACCESS_VIOLATION".to_string())]);
+        assert_eq!(names(0x8007_0005), [("HRESULT", "HRESULT_FROM_WIN32(ERROR_ACCESS_DENIED)".to_string())]);
+        assert_eq!(names(0xd000_0022), [("HRESULT", "HRESULT_FROM_NT(STATUS_ACCESS_DENIED)".to_string())]);
+        assert_eq!(names(0x1_0000_0000), []);
+        let meanings = whatis(0xc0de_0001);
+        assert_eq!(meanings.len(), 1);
+        assert_eq!(meanings[0].detail.as_deref(), Some("error, facility 0xde, code 0x1"));
+    }
+
+    #[test]
+    fn test_hresult_detail() {
+        assert_eq!(hresult_detail(0x8007_0005), "failure, facility WIN32 (0x7), code 0x5");
+        assert_eq!(hresult_detail(0x8004_4005), "failure, facility ITF (0x4), code 0x4005");
+    }
+}
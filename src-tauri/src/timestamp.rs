@@ -0,0 +1,275 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::CorkError;
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Seconds from 1601-01-01, where FILETIME counts from, to the Unix epoch.
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+/// Seconds from the Unix epoch to 2001-01-01, where Mac absolute time counts
+/// from.
+const MAC_UNIX_OFFSET: i64 = 978_307_200;
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// An Epoch is a way of storing a point in time as a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Epoch {
+    /// Seconds since 1970-01-01
+    Unix,
+    /// Milliseconds since 1970-01-01
+    UnixMs,
+    /// Nanoseconds since 1970-01-01
+    UnixNs,
+    /// 100 ns intervals since 1601-01-01, as Windows keeps file times
+    FileTime,
+    /// Seconds since 2001-01-01, as Core Foundation keeps absolute time
+    MacTime,
+    /// The FAT date in the high and the time in the low 16 bits, with 2 s
+    /// precision, from 1980 to 2107
+    DosTime,
+}
+
+impl Epoch {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Epoch::Unix => "unix",
+            Epoch::UnixMs => "unix_ms",
+            Epoch::UnixNs => "unix_ns",
+            Epoch::FileTime => "filetime",
+            Epoch::MacTime => "mactime",
+            Epoch::DosTime => "dostime",
+        }
+    }
+
+    /// decode gives the seconds since the Unix epoch and the nanoseconds
+    /// past them that `value` stands for.
+    pub fn decode(&self, value: i64) -> Option<(i64, u32)> {
+        let split = |value: i64, per_sec: i64| {
+            let nanos = value.rem_euclid(per_sec) * (NANOS_PER_SEC / per_sec);
+            (value.div_euclid(per_sec), nanos as u32)
+        };
+        match self {
+            Epoch::Unix => Some((value, 0)),
+            Epoch::UnixMs => Some(split(value, 1_000)),
+            Epoch::UnixNs => Some(split(value, NANOS_PER_SEC)),
+            Epoch::FileTime => {
+                let (secs, nanos) = split(value, 10_000_000);
+                Some((secs - FILETIME_UNIX_OFFSET, nanos))
+            }
+            Epoch::MacTime => value.checked_add(MAC_UNIX_OFFSET).map(|secs| (secs, 0)),
+            Epoch::DosTime => {
+                let (date, time) = ((value >> 16) & 0xffff, value & 0xffff);
+                let (year, month, day) = (1980 + (date >> 9), (date >> 5) & 0xf, date & 0x1f);
+                let (hour, min, sec) = (time >> 11, (time >> 5) & 0x3f, (time & 0x1f) * 2);
+                if value >> 32 != 0 || hour > 23 || min > 59 || sec > 59 {
+                    return None;
+                }
+                let days = days_from_civil(year, month, day)?;
+                Some((days * 86_400 + hour * 3_600 + min * 60 + sec, 0))
+            }
+        }
+    }
+
+    /// encode is the inverse of decode. Precision the epoch doesn't have
+    /// is dropped.
+    pub fn encode(&self, secs: i64, nanos: u32) -> Result<i64, CorkError> {
+        let out_of_range = || CorkError::Eval(format!("the time is out of range for {}", self));
+        let join = |secs: i64, per_sec: i64| {
+            secs.checked_mul(per_sec)
+                .and_then(|value| value.checked_add(nanos as i64 / (NANOS_PER_SEC / per_sec)))
+                .ok_or_else(out_of_range)
+        };
+        match self {
+            Epoch::Unix => Ok(secs),
+            Epoch::UnixMs => join(secs, 1_000),
+            Epoch::UnixNs => join(secs, NANOS_PER_SEC),
+            Epoch::FileTime => join(secs.checked_add(FILETIME_UNIX_OFFSET).ok_or_else(out_of_range)?, 10_000_000),
+            Epoch::MacTime => secs.checked_sub(MAC_UNIX_OFFSET).ok_or_else(out_of_range),
+            Epoch::DosTime => {
+                let (days, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+                let (year, month, day) = civil_from_days(days);
+                if !(1980..=2107).contains(&year) {
+                    return Err(out_of_range());
+                }
+                let date = (year - 1980) << 9 | month << 5 | day;
+                let time = (time / 3_600) << 11 | (time / 60 % 60) << 5 | ((time % 60) / 2);
+                Ok(date << 16 | time)
+            }
+        }
+    }
+
+    /// fmt shows `value` as an ISO-8601 UTC time. Times outside years 0 to
+    /// 9999 are not shown.
+    pub fn fmt(&self, value: i64) -> String {
+        self.decode(value)
+            .and_then(|(secs, nanos)| fmt_iso(secs, nanos))
+            .unwrap_or_else(|| format!("{:#x} is not a valid {} time", value, self))
+    }
+}
+
+impl fmt::Display for Epoch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Epoch {
+    type Err = CorkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Epoch::iter()
+            .find(|epoch| epoch.name() == s)
+            .ok_or_else(|| CorkError::Eval(format!("{} is not a known timestamp", s)))
+    }
+}
+
+/// days_from_civil counts the days from 1970-01-01 to a date of the
+/// proleptic Gregorian calendar (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=days_in_month).contains(&day) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + (month <= 2) as i64, month, day)
+}
+
+/// fmt_iso writes a time as `YYYY-MM-DDTHH:MM:SSZ`, with as many fractional
+/// digits as the nanoseconds need.
+pub fn fmt_iso(secs: i64, nanos: u32) -> Option<String> {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+    let time = secs.rem_euclid(86_400);
+    let mut iso = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time / 60 % 60,
+        time % 60
+    );
+    if nanos != 0 {
+        iso.push_str(format!(".{:09}", nanos).trim_end_matches('0'));
+    }
+    iso.push('Z');
+    Some(iso)
+}
+
+/// parse_iso reads `YYYY-MM-DD`, optionally followed by `T` (or a space)
+/// and `HH:MM[:SS[.fraction]]`, and by `Z` or a `+HH:MM` offset. Times
+/// without an offset are UTC.
+pub fn parse_iso(text: &str) -> Result<(i64, u32), CorkError> {
+    let invalid = || CorkError::Eval(format!("{} is not an ISO-8601 time", text));
+    let num = |digits: &str, len: usize| -> Result<i64, CorkError> {
+        if digits.len() != len || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        digits.parse().map_err(|_| invalid())
+    };
+    let text = text.trim();
+    let (date, rest) = text.split_at(text.find(['T', 't', ' ']).unwrap_or(text.len()));
+    let mut date_parts = date.split('-');
+    let (year, month, day) = match (date_parts.next(), date_parts.next(), date_parts.next(), date_parts.next()) {
+        (Some(year), Some(month), Some(day), None) => (num(year, 4)?, num(month, 2)?, num(day, 2)?),
+        _ => return Err(invalid()),
+    };
+    let days = days_from_civil(year, month, day).ok_or_else(invalid)?;
+
+    let rest = rest.get(1..).unwrap_or("");
+    let (time, offset) = match rest.find(['Z', 'z', '+', '-']) {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+    let (mut secs, mut nanos) = (days * 86_400, 0u32);
+    if !time.is_empty() {
+        let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+        let parts: Vec<_> = time.split(':').collect();
+        let (hour, min, sec) = match parts.as_slice() {
+            [hour, min] => (num(hour, 2)?, num(min, 2)?, 0),
+            [hour, min, sec] => (num(hour, 2)?, num(min, 2)?, num(sec, 2)?),
+            _ => return Err(invalid()),
+        };
+        if hour > 23 || min > 59 || sec > 59 {
+            return Err(invalid());
+        }
+        secs += hour * 3_600 + min * 60 + sec;
+        if !fraction.is_empty() {
+            let digits = &fraction[..fraction.len().min(9)];
+            nanos = (num(digits, digits.len())? * 10i64.pow(9 - digits.len() as u32)) as u32;
+        }
+    }
+    match offset {
+        "" | "Z" | "z" => {}
+        _ => {
+            let (sign, hhmm) = offset.split_at(1);
+            let (hour, min) = hhmm.split_once(':').ok_or_else(invalid)?;
+            let offset = num(hour, 2)? * 3_600 + num(min, 2)? * 60;
+            secs += if sign == "+" { -offset } else { offset };
+        }
+    }
+    Ok((secs, nanos))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_epochs() {
+        let cases = [
+            (Epoch::Unix, 1_686_565_882, "2023-06-12T10:31:22Z"),
+            (Epoch::Unix, -1, "1969-12-31T23:59:59Z"),
+            (Epoch::UnixMs, 1_686_565_882_250, "2023-06-12T10:31:22.25Z"),
+            (Epoch::UnixNs, 1_686_565_882_000_000_001, "2023-06-12T10:31:22.000000001Z"),
+            (Epoch::FileTime, 0x01d9_a3c2_e4f5_a600, "2023-06-20T22:02:24.4427264Z"),
+            (Epoch::FileTime, 116_444_736_000_000_000, "1970-01-01T00:00:00Z"),
+            (Epoch::MacTime, 0, "2001-01-01T00:00:00Z"),
+            (Epoch::DosTime, 0x56cc_53cb, "2023-06-12T10:30:22Z"),
+        ];
+        for (epoch, value, iso) in cases {
+            assert_eq!(epoch.fmt(value), iso, "{}", epoch);
+            let (secs, nanos) = parse_iso(iso).unwrap();
+            assert_eq!(epoch.encode(secs, nanos), Ok(value), "{}", epoch);
+        }
+        assert_eq!(Epoch::DosTime.fmt(0), "0x0 is not a valid dostime time");
+        assert!(Epoch::DosTime.encode(0, 0).is_err());
+        assert_eq!("filetime".parse(), Ok(Epoch::FileTime));
+    }
+
+    #[test]
+    fn test_parse_iso() {
+        assert_eq!(parse_iso("1970-01-01"), Ok((0, 0)));
+        assert_eq!(parse_iso("1970-01-01 00:01"), Ok((60, 0)));
+        assert_eq!(parse_iso("1970-01-01T02:00:00+02:00"), Ok((0, 0)));
+        assert_eq!(parse_iso("1969-12-31T23:00:00.5-01:00"), Ok((0, 500_000_000)));
+        assert_eq!(parse_iso("2000-02-29T00:00:00Z"), Ok((951_782_400, 0)));
+        assert!(parse_iso("2001-02-29").is_err());
+        assert!(parse_iso("2001-1-01").is_err());
+        assert!(parse_iso("2001-01-01T24:00").is_err());
+        assert!(parse_iso("yesterday").is_err());
+    }
+}
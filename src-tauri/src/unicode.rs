@@ -0,0 +1,289 @@
+use crate::{error::CorkError, format::align_columns};
+
+const C0_NAMES: [&str; 32] = [
+    "NULL",
+    "START OF HEADING",
+    "START OF TEXT",
+    "END OF TEXT",
+    "END OF TRANSMISSION",
+    "ENQUIRY",
+    "ACKNOWLEDGE",
+    "BELL",
+    "BACKSPACE",
+    "CHARACTER TABULATION",
+    "LINE FEED",
+    "LINE TABULATION",
+    "FORM FEED",
+    "CARRIAGE RETURN",
+    "SHIFT OUT",
+    "SHIFT IN",
+    "DATA LINK ESCAPE",
+    "DEVICE CONTROL ONE",
+    "DEVICE CONTROL TWO",
+    "DEVICE CONTROL THREE",
+    "DEVICE CONTROL FOUR",
+    "NEGATIVE ACKNOWLEDGE",
+    "SYNCHRONOUS IDLE",
+    "END OF TRANSMISSION BLOCK",
+    "CANCEL",
+    "END OF MEDIUM",
+    "SUBSTITUTE",
+    "ESCAPE",
+    "INFORMATION SEPARATOR FOUR",
+    "INFORMATION SEPARATOR THREE",
+    "INFORMATION SEPARATOR TWO",
+    "INFORMATION SEPARATOR ONE",
+];
+
+const ASCII_SYMBOL_NAMES: [(char, &str); 33] = [
+    (' ', "SPACE"),
+    ('!', "EXCLAMATION MARK"),
+    ('"', "QUOTATION MARK"),
+    ('#', "NUMBER SIGN"),
+    ('$', "DOLLAR SIGN"),
+    ('%', "PERCENT SIGN"),
+    ('&', "AMPERSAND"),
+    ('\'', "APOSTROPHE"),
+    ('(', "LEFT PARENTHESIS"),
+    (')', "RIGHT PARENTHESIS"),
+    ('*', "ASTERISK"),
+    ('+', "PLUS SIGN"),
+    (',', "COMMA"),
+    ('-', "HYPHEN-MINUS"),
+    ('.', "FULL STOP"),
+    ('/', "SOLIDUS"),
+    (':', "COLON"),
+    (';', "SEMICOLON"),
+    ('<', "LESS-THAN SIGN"),
+    ('=', "EQUALS SIGN"),
+    ('>', "GREATER-THAN SIGN"),
+    ('?', "QUESTION MARK"),
+    ('@', "COMMERCIAL AT"),
+    ('[', "LEFT SQUARE BRACKET"),
+    ('\\', "REVERSE SOLIDUS"),
+    (']', "RIGHT SQUARE BRACKET"),
+    ('^', "CIRCUMFLEX ACCENT"),
+    ('_', "LOW LINE"),
+    ('`', "GRAVE ACCENT"),
+    ('{', "LEFT CURLY BRACKET"),
+    ('|', "VERTICAL LINE"),
+    ('}', "RIGHT CURLY BRACKET"),
+    ('~', "TILDE"),
+];
+
+const DIGIT_NAMES: [&str; 10] = ["ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE"];
+
+/// The symbols of U+00A0 to U+00BF.
+const LATIN1_SYMBOL_NAMES: [&str; 32] = [
+    "NO-BREAK SPACE",
+    "INVERTED EXCLAMATION MARK",
+    "CENT SIGN",
+    "POUND SIGN",
+    "CURRENCY SIGN",
+    "YEN SIGN",
+    "BROKEN BAR",
+    "SECTION SIGN",
+    "DIAERESIS",
+    "COPYRIGHT SIGN",
+    "FEMININE ORDINAL INDICATOR",
+    "LEFT-POINTING DOUBLE ANGLE QUOTATION MARK",
+    "NOT SIGN",
+    "SOFT HYPHEN",
+    "REGISTERED SIGN",
+    "MACRON",
+    "DEGREE SIGN",
+    "PLUS-MINUS SIGN",
+    "SUPERSCRIPT TWO",
+    "SUPERSCRIPT THREE",
+    "ACUTE ACCENT",
+    "MICRO SIGN",
+    "PILCROW SIGN",
+    "MIDDLE DOT",
+    "CEDILLA",
+    "SUPERSCRIPT ONE",
+    "MASCULINE ORDINAL INDICATOR",
+    "RIGHT-POINTING DOUBLE ANGLE QUOTATION MARK",
+    "VULGAR FRACTION ONE QUARTER",
+    "VULGAR FRACTION ONE HALF",
+    "VULGAR FRACTION THREE QUARTERS",
+    "INVERTED QUESTION MARK",
+];
+
+/// The letters of U+00C0 to U+00DE, which U+00E0 to U+00FE repeat in small
+/// letters. U+00D7 and U+00F7 are signs instead.
+const LATIN1_LETTER_NAMES: [&str; 31] = [
+    "A WITH GRAVE",
+    "A WITH ACUTE",
+    "A WITH CIRCUMFLEX",
+    "A WITH TILDE",
+    "A WITH DIAERESIS",
+    "A WITH RING ABOVE",
+    "AE",
+    "C WITH CEDILLA",
+    "E WITH GRAVE",
+    "E WITH ACUTE",
+    "E WITH CIRCUMFLEX",
+    "E WITH DIAERESIS",
+    "I WITH GRAVE",
+    "I WITH ACUTE",
+    "I WITH CIRCUMFLEX",
+    "I WITH DIAERESIS",
+    "ETH",
+    "N WITH TILDE",
+    "O WITH GRAVE",
+    "O WITH ACUTE",
+    "O WITH CIRCUMFLEX",
+    "O WITH TILDE",
+    "O WITH DIAERESIS",
+    "",
+    "O WITH STROKE",
+    "U WITH GRAVE",
+    "U WITH ACUTE",
+    "U WITH CIRCUMFLEX",
+    "U WITH DIAERESIS",
+    "Y WITH ACUTE",
+    "THORN",
+];
+
+/// Characters past Latin-1 that tend to turn up in strings from binaries.
+const OTHER_NAMES: [(char, &str); 22] = [
+    ('\u{200b}', "ZERO WIDTH SPACE"),
+    ('\u{200c}', "ZERO WIDTH NON-JOINER"),
+    ('\u{200d}', "ZERO WIDTH JOINER"),
+    ('\u{200e}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200f}', "RIGHT-TO-LEFT MARK"),
+    ('\u{2013}', "EN DASH"),
+    ('\u{2014}', "EM DASH"),
+    ('\u{2018}', "LEFT SINGLE QUOTATION MARK"),
+    ('\u{2019}', "RIGHT SINGLE QUOTATION MARK"),
+    ('\u{201c}', "LEFT DOUBLE QUOTATION MARK"),
+    ('\u{201d}', "RIGHT DOUBLE QUOTATION MARK"),
+    ('\u{2022}', "BULLET"),
+    ('\u{2026}', "HORIZONTAL ELLIPSIS"),
+    ('\u{2028}', "LINE SEPARATOR"),
+    ('\u{2029}', "PARAGRAPH SEPARATOR"),
+    ('\u{202e}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{20ac}', "EURO SIGN"),
+    ('\u{2122}', "TRADE MARK SIGN"),
+    ('\u{feff}', "ZERO WIDTH NO-BREAK SPACE"),
+    ('\u{fffd}', "REPLACEMENT CHARACTER"),
+    ('\u{1f600}', "GRINNING FACE"),
+    ('\u{1f642}', "SLIGHTLY SMILING FACE"),
+];
+
+/// name gives the Unicode name of `c`, or the usual alias of a C0 control.
+/// Only ASCII, Latin-1, CJK ideographs and a few common characters are known.
+pub fn name(c: char) -> Option<String> {
+    let cp = c as u32;
+    let name = match c {
+        '\0'..='\x1f' => C0_NAMES[cp as usize].to_string(),
+        '\x7f' => "DELETE".to_string(),
+        '0'..='9' => format!("DIGIT {}", DIGIT_NAMES[(cp - '0' as u32) as usize]),
+        'A'..='Z' => format!("LATIN CAPITAL LETTER {}", c),
+        'a'..='z' => format!("LATIN SMALL LETTER {}", c.to_ascii_uppercase()),
+        '\u{a0}'..='\u{bf}' => LATIN1_SYMBOL_NAMES[(cp - 0xa0) as usize].to_string(),
+        '\u{d7}' => "MULTIPLICATION SIGN".to_string(),
+        '\u{f7}' => "DIVISION SIGN".to_string(),
+        '\u{df}' => "LATIN SMALL LETTER SHARP S".to_string(),
+        '\u{ff}' => "LATIN SMALL LETTER Y WITH DIAERESIS".to_string(),
+        '\u{c0}'..='\u{de}' => format!("LATIN CAPITAL LETTER {}", LATIN1_LETTER_NAMES[(cp - 0xc0) as usize]),
+        '\u{e0}'..='\u{fe}' => format!("LATIN SMALL LETTER {}", LATIN1_LETTER_NAMES[(cp - 0xe0) as usize]),
+        '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' => format!("CJK UNIFIED IDEOGRAPH-{:04X}", cp),
+        _ => {
+            let table = ASCII_SYMBOL_NAMES.iter().chain(OTHER_NAMES.iter());
+            return table.into_iter().find(|(known, _)| *known == c).map(|(_, name)| name.to_string());
+        }
+    };
+    Some(name)
+}
+
+/// describe gives the U+ notation of `c`, the character itself unless it is
+/// a control, and its name if known. Missing parts are left empty.
+pub fn describe(c: char) -> [String; 3] {
+    [
+        format!("U+{:04X}", c as u32),
+        if c.is_control() { String::new() } else { format!("'{}'", c) },
+        name(c).unwrap_or_default(),
+    ]
+}
+
+/// fmt_char renders a value as the character it is the code point of.
+pub fn fmt_char(value: u64) -> String {
+    match u32::try_from(value).ok().and_then(char::from_u32) {
+        Some(c) => describe(c).into_iter().filter(|cell| !cell.is_empty()).collect::<Vec<_>>().join(" "),
+        None => format!("{:#x} is not a Unicode character", value),
+    }
+}
+
+pub fn to_char(value: i64) -> Result<char, CorkError> {
+    u32::try_from(value)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| CorkError::Eval(format!("{:#x} is not a Unicode character", value)))
+}
+
+pub fn encode_utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+pub fn decode_utf8(bytes: &[u8]) -> Result<String, CorkError> {
+    String::from_utf8(bytes.to_vec()).map_err(|err| match err.utf8_error().error_len() {
+        Some(_) => CorkError::Eval(format!("invalid UTF-8 at byte {}", err.utf8_error().valid_up_to())),
+        None => CorkError::Truncated("utf8".to_string()),
+    })
+}
+
+pub fn decode_utf16le(bytes: &[u8]) -> Result<String, CorkError> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(CorkError::Truncated("utf16le".to_string()));
+    }
+    let units: Vec<u16> = bytes.chunks(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    char::decode_utf16(units.iter().copied())
+        .enumerate()
+        .map(|(idx, c)| {
+            c.map_err(|err| {
+                CorkError::Eval(format!("unpaired surrogate {:#06x} in UTF-16 at unit {}", err.unpaired_surrogate(), idx))
+            })
+        })
+        .collect()
+}
+
+/// code_points lays out the characters of `text` one per line, as describe
+/// does.
+pub fn code_points(text: &str) -> String {
+    let rows: Vec<_> = text.chars().map(|c| describe(c).to_vec()).collect();
+    align_columns(&rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(name('A').as_deref(), Some("LATIN CAPITAL LETTER A"));
+        assert_eq!(name('7').as_deref(), Some("DIGIT SEVEN"));
+        assert_eq!(name('\n').as_deref(), Some("LINE FEED"));
+        assert_eq!(name('é').as_deref(), Some("LATIN SMALL LETTER E WITH ACUTE"));
+        assert_eq!(name('×').as_deref(), Some("MULTIPLICATION SIGN"));
+        assert_eq!(name('Þ').as_deref(), Some("LATIN CAPITAL LETTER THORN"));
+        assert_eq!(name('~').as_deref(), Some("TILDE"));
+        assert_eq!(name('中').as_deref(), Some("CJK UNIFIED IDEOGRAPH-4E2D"));
+        assert_eq!(name('\u{1f600}').as_deref(), Some("GRINNING FACE"));
+        assert_eq!(name('\u{0416}'), None);
+
+        assert_eq!(fmt_char(0xe9), "U+00E9 'é' LATIN SMALL LETTER E WITH ACUTE");
+        assert_eq!(fmt_char(0x0a), "U+000A LINE FEED");
+        assert_eq!(fmt_char(0xd800), "0xd800 is not a Unicode character");
+    }
+
+    #[test]
+    fn test_utf16le() {
+        assert_eq!(encode_utf16le("é\u{1f600}"), [0xe9, 0x00, 0x3d, 0xd8, 0x00, 0xde]);
+        assert_eq!(decode_utf16le(&[0xe9, 0x00, 0x3d, 0xd8, 0x00, 0xde]).unwrap(), "é\u{1f600}");
+        assert!(decode_utf16le(&[0x3d, 0xd8]).is_err());
+        assert_eq!(decode_utf16le(&[0x41]), Err(CorkError::Truncated("utf16le".to_string())));
+        assert_eq!(decode_utf8(b"\xc3"), Err(CorkError::Truncated("utf8".to_string())));
+        assert!(decode_utf8(b"\xc3A").is_err());
+    }
+}
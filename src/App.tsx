@@ -1,8 +1,12 @@
 import "./App.css";
 import Header from './component/Header';
 import ResultDisplay from './component/ResultDisplay'; 
+import StatusPanel from './component/StatusPanel';
 import BinaryUI from './component/BinaryUI';
 import BitWidthToggle from './component/BitWidthToggle';
+import HistoryPanel from './component/HistoryPanel';
+import LayoutSelect from './component/LayoutSelect';
+import PrefixSelect from './component/PrefixSelect';
 import { Box } from '@mui/material';
 
 function App() {
@@ -12,15 +16,23 @@ function App() {
       <Box mb={2}>
         <Header />
       </Box>
-      <Box mb={2}>
+      <Box mb={2} display="flex" justifyContent="center" alignItems="center" gap={2}>
         <BitWidthToggle />
+        <LayoutSelect />
+        <PrefixSelect />
       </Box>
       <Box mb={2}>
         <BinaryUI />
       </Box>
-      <Box>
+      <Box mb={2}>
         <ResultDisplay />
       </Box>
+      <Box mb={2}>
+        <StatusPanel />
+      </Box>
+      <Box>
+        <HistoryPanel />
+      </Box>
     </main>
   );
 }
@@ -1,9 +1,11 @@
 import React from 'react';
-import { Box, ToggleButton } from '@mui/material';
+import { Box, ToggleButton, Tooltip } from '@mui/material';
 import { styled } from '@mui/material/styles';
 import { useEffect, useState } from 'react';
 import PubSub from 'pubsub-js';
-import { CalculateResultMessage } from '../types';
+import { invoke } from "@tauri-apps/api/core";
+import { CalculateResultMessage, LayoutField, ValueResult } from '../types';
+import { parseResult, toSigned } from '../supportFunctions';
 
 // Styled container for the binary display
 const BinaryContainer = styled(Box)(({ theme }) => ({
@@ -61,10 +63,22 @@ const BitIndex = styled(Box)({
   marginTop: '4px',
 });
 
+// Styled field name label, shown under the highest bit of a layout field
+const FieldLabel = styled(Box)(({ theme }) => ({
+  fontSize: '0.6rem',
+  height: '0.8rem',
+  maxWidth: '36px',
+  overflow: 'visible',
+  whiteSpace: 'nowrap',
+  color: theme.palette.secondary.main,
+}));
+
 const BinaryUI: React.FC = () => {
   const [complementResult, setComplementResult] = useState<bigint | null>(0n); // 补码 state
   const [hasError, setHasError] = useState<boolean>(false);
   const [selectedBitWidth, setSelectedBitWidth] = useState<number>(64);
+  const [fields, setFields] = useState<LayoutField[]>([]);
+  const [prefix, setPrefix] = useState<number | null>(null);
 
   useEffect(() => {
     const calculateToken = PubSub.subscribe('CALCULATE_RESULT', (_msg: string, data: CalculateResultMessage) => {
@@ -86,24 +100,36 @@ const BinaryUI: React.FC = () => {
       }
     });
 
+    const layoutToken = PubSub.subscribe('LAYOUT_CHANGED', (_msg: string, data: LayoutField[]) => {
+      setFields(data);
+    });
+
+    const prefixToken = PubSub.subscribe('PREFIX_CHANGED', (_msg: string, data: number | null) => {
+      setPrefix(data);
+    });
+
     return () => {
       PubSub.unsubscribe(calculateToken);
+      PubSub.unsubscribe(layoutToken);
+      PubSub.unsubscribe(prefixToken);
     };
   }, []);
 
-  const handleBitToggle = (bitPosition: number) => {
+  // The backend owns the current value, so width rules match the command line
+  const handleBitToggle = async (bitPosition: number) => {
     if (complementResult === null) return;
-    const mask = 1n << BigInt(bitPosition);
-    const newCompl = complementResult ^ mask;
-    let newValue: bigint;
-    if (newCompl >= (1n << BigInt(selectedBitWidth - 1))) {
-      newValue = newCompl - (1n << BigInt(selectedBitWidth));
-    } else {
-      newValue = newCompl;
-    }
-
-    const message: CalculateResultMessage = { bigIntResult: newValue, error: null, bitWidth: selectedBitWidth }
+    try {
+      const res: ValueResult = await invoke('edit_bits', {
+        edit: { Flip: [bitPosition, bitPosition] },
+        width: selectedBitWidth,
+      });
+      // edited bits come back unsigned at the width, the panels expect a signed value
+      const message: CalculateResultMessage = { bigIntResult: toSigned(parseResult(res.value), res.width), error: null, bitWidth: res.width }
+      PubSub.publish('CALCULATE_RESULT', message);
+    } catch (err) {
+      const message: CalculateResultMessage = { bigIntResult: null, error: `Error: ${err}`, bitWidth: selectedBitWidth }
       PubSub.publish('CALCULATE_RESULT', message);
+    }
   };
 
   const getBit = (position: number): boolean => {
@@ -113,6 +139,18 @@ const BinaryUI: React.FC = () => {
     return (complementResult / (1n << BigInt(position)) % 2n) === 1n;
   };
 
+  const fieldAt = (position: number): LayoutField | undefined =>
+    fields.find((field) => field.bits.lo <= position && position <= field.bits.hi);
+
+  const fieldTitle = (field: LayoutField): string => {
+    const { lo, hi } = field.bits;
+    const bits = lo === hi ? `${lo}` : `${hi}:${lo}`;
+    return `${field.name} [${bits}]${field.description ? ` ${field.description}` : ''}`;
+  };
+
+  // A prefix counts from the top of the current width, so /24 at width 32 is an IPv4 network
+  const lastNetworkBit = prefix !== null && prefix <= selectedBitWidth ? selectedBitWidth - prefix : null;
+
   // Generate 64 bit positions in descending order and split into 4 rows of 16 bits each.
   const allBits = Array.from({ length: 64 }, (_, i) => i).reverse();
   const rows = [];
@@ -131,17 +169,28 @@ const BinaryUI: React.FC = () => {
               <BitGroup key={groupIdx}>
                 {group.map((position) => {
                   const isDisabled = position >= selectedBitWidth || hasError;
+                  const field = fieldAt(position);
                   return (
-                    <Box key={position}>
-                      <BitButton
-                        value={position.toString()}
-                        selected={!hasError && !isDisabled && getBit(position)}
-                        onChange={() => handleBitToggle(position)}
-                        disabled={isDisabled}
-                      >
-                        {isDisabled ? '0' : (getBit(position) ? '1' : '0')}
-                      </BitButton>
+                    <Box
+                      key={position}
+                      sx={position === lastNetworkBit ? { borderRight: 3, borderColor: 'secondary.main' } : undefined}
+                    >
+                      <Tooltip title={field ? fieldTitle(field) : ''}>
+                        <span>
+                          <BitButton
+                            value={position.toString()}
+                            selected={!hasError && !isDisabled && getBit(position)}
+                            onChange={() => handleBitToggle(position)}
+                            disabled={isDisabled}
+                          >
+                            {isDisabled ? '0' : (getBit(position) ? '1' : '0')}
+                          </BitButton>
+                        </span>
+                      </Tooltip>
                       <BitIndex>{position}</BitIndex>
+                      {fields.length > 0 && (
+                        <FieldLabel>{field && field.bits.hi === position ? field.name : ''}</FieldLabel>
+                      )}
                     </Box>
                   );
                 })}
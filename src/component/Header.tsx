@@ -4,8 +4,8 @@ import { invoke } from "@tauri-apps/api/core";
 import { Box, Button, TextField, ToggleButtonGroup, ToggleButton, IconButton, Tooltip } from '@mui/material';
 import HelpOutlineIcon from '@mui/icons-material/HelpOutline';
 import { styled } from '@mui/material/styles';
-import { CalculateResultMessage } from '../types';
-import { getMinimalBitWidth } from '../supportFunctions';
+import { CalculateResultMessage, EvalResult } from '../types';
+import { resultMessage } from '../supportFunctions';
 
 // Container for the header elements
 const Container = styled(Box)(({ theme }) => ({
@@ -64,26 +64,12 @@ export default function Header() {
 
   const handleEvaluate = async () => {
     try {
-      const res: string = await invoke('evaluate_expression', { 
+      const res: EvalResult = await invoke('evaluate_expression', { 
         exprStr: expression,
         options: { mode }
       });
-      // Process hexadecimal strings with a negative sign
-      let isNegative = false;
-      let processedRes = res;
-      if (processedRes.startsWith('-')) {
-        isNegative = true;
-        processedRes = processedRes.substring(1);
-      }
-      
-       // Create a BigInt using the processed string
-      const value = BigInt(processedRes);
-      // If the original string is negative, convert value to a negative number
-      const finalValue = isNegative ? -value : value;
-      // console.log("Header:", finalValue)
-      const bitWidth = getMinimalBitWidth(finalValue)
-      const message: CalculateResultMessage = { bigIntResult: finalValue, error: null, bitWidth }
-      PubSub.publish('CALCULATE_RESULT', message);
+      PubSub.publish('CALCULATE_RESULT', resultMessage(res));
+      PubSub.publish('HISTORY_UPDATED');
     } catch (err) {
       const message: CalculateResultMessage = { bigIntResult: null, error: `Error: ${err}`, bitWidth: 8 }
       PubSub.publish('CALCULATE_RESULT', message);
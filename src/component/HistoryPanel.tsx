@@ -0,0 +1,113 @@
+import React, { useEffect, useState } from 'react';
+import PubSub from 'pubsub-js';
+import { invoke } from "@tauri-apps/api/core";
+import { Box, IconButton, List, ListItem, ListItemButton, ListItemText, TextField, Tooltip } from '@mui/material';
+import PushPinIcon from '@mui/icons-material/PushPin';
+import PushPinOutlinedIcon from '@mui/icons-material/PushPinOutlined';
+import DeleteOutlineIcon from '@mui/icons-material/DeleteOutline';
+import { styled } from '@mui/material/styles';
+import { CalculateResultMessage, EvalResult, HistoryItem } from '../types';
+import { resultMessage } from '../supportFunctions';
+
+const HistoryBox = styled(Box)(({ theme }) => ({
+  padding: theme.spacing(1),
+  border: `1px solid ${theme.palette.divider}`,
+  borderRadius: 10,
+  maxWidth: '600px',
+  minWidth: '440px',
+  margin: '0 auto',
+}));
+
+const HistoryList = styled(List)({
+  maxHeight: '200px',
+  overflowY: 'auto',
+  fontFamily: 'monospace',
+});
+
+const HistoryPanel: React.FC = () => {
+  const [items, setItems] = useState<HistoryItem[]>([]);
+  const [query, setQuery] = useState('');
+
+  const refresh = async (search: string) => {
+    const res: HistoryItem[] = search.trim() === ''
+      ? await invoke('list_history')
+      : await invoke('search_history', { query: search });
+    // Newest entries first, pinned entries on top
+    res.reverse();
+    res.sort((a, b) => Number(b.pinned) - Number(a.pinned));
+    setItems(res);
+  };
+
+  useEffect(() => {
+    refresh(query);
+    const token = PubSub.subscribe('HISTORY_UPDATED', () => refresh(query));
+    return () => {
+      PubSub.unsubscribe(token);
+    };
+  }, [query]);
+
+  const handleReevaluate = async (id: number) => {
+    try {
+      const res: EvalResult = await invoke('reevaluate_history', { id });
+      PubSub.publish('CALCULATE_RESULT', resultMessage(res));
+    } catch (err) {
+      const message: CalculateResultMessage = { bigIntResult: null, error: `Error: ${err}`, bitWidth: 8 }
+      PubSub.publish('CALCULATE_RESULT', message);
+    }
+    refresh(query);
+  };
+
+  const handlePin = async (item: HistoryItem) => {
+    await invoke('pin_history', { id: item.id, pinned: !item.pinned });
+    refresh(query);
+  };
+
+  const handleDelete = async (id: number) => {
+    await invoke('delete_history', { id });
+    refresh(query);
+  };
+
+  return (
+    <HistoryBox>
+      <TextField
+        size="small"
+        fullWidth
+        label="Search history"
+        value={query}
+        onChange={(e) => setQuery(e.currentTarget.value)}
+      />
+      <HistoryList dense>
+        {items.map((item) => (
+          <ListItem
+            key={item.id}
+            disablePadding
+            secondaryAction={
+              <>
+                <Tooltip title={item.pinned ? 'Unpin' : 'Pin'}>
+                  <IconButton size="small" onClick={() => handlePin(item)}>
+                    {item.pinned ? <PushPinIcon fontSize="small" /> : <PushPinOutlinedIcon fontSize="small" />}
+                  </IconButton>
+                </Tooltip>
+                <Tooltip title="Delete">
+                  <IconButton size="small" onClick={() => handleDelete(item.id)}>
+                    <DeleteOutlineIcon fontSize="small" />
+                  </IconButton>
+                </Tooltip>
+              </>
+            }
+          >
+            <ListItemButton onClick={() => handleReevaluate(item.id)}>
+              <ListItemText
+                className="selectable-text"
+                primary={`$${item.id}  ${item.input}`}
+                secondary={`= ${item.result}  (${new Date(item.timestamp * 1000).toLocaleString()})`}
+              />
+            </ListItemButton>
+          </ListItem>
+        ))}
+      </HistoryList>
+    </HistoryBox>
+  );
+};
+
+export default HistoryPanel;
@@ -0,0 +1,41 @@
+import React, { useEffect, useState } from 'react';
+import PubSub from 'pubsub-js';
+import { invoke } from "@tauri-apps/api/core";
+import { FormControl, InputLabel, MenuItem, Select, SelectChangeEvent } from '@mui/material';
+import { LayoutField } from '../types';
+
+// Lets the user pick a register layout, the bit view then labels its fields
+const LayoutSelect: React.FC = () => {
+  const [layouts, setLayouts] = useState<string[]>([]);
+  const [selected, setSelected] = useState('');
+
+  useEffect(() => {
+    invoke<string[]>('list_layouts').then(setLayouts);
+  }, []);
+
+  const handleChange = async (event: SelectChangeEvent) => {
+    const name = event.target.value;
+    setSelected(name);
+    const fields: LayoutField[] = name === '' ? [] : await invoke('layout_fields', { name });
+    PubSub.publish('LAYOUT_CHANGED', fields);
+  };
+
+  return (
+    <FormControl size="small" sx={{ minWidth: 160 }} disabled={layouts.length === 0}>
+      <InputLabel id="layout-select-label">Layout</InputLabel>
+      <Select
+        labelId="layout-select-label"
+        value={selected}
+        label="Layout"
+        onChange={handleChange}
+      >
+        <MenuItem value=""><em>None</em></MenuItem>
+        {layouts.map((name) => (
+          <MenuItem key={name} value={name}>{name}</MenuItem>
+        ))}
+      </Select>
+    </FormControl>
+  );
+};
+
+export default LayoutSelect;
@@ -0,0 +1,33 @@
+import React, { useState } from 'react';
+import PubSub from 'pubsub-js';
+import { FormControl, InputLabel, MenuItem, Select, SelectChangeEvent } from '@mui/material';
+
+// Lets the user pick a CIDR prefix length, the bit view then marks where the network part ends
+const PrefixSelect: React.FC = () => {
+  const [selected, setSelected] = useState('');
+
+  const handleChange = (event: SelectChangeEvent) => {
+    const prefix = event.target.value;
+    setSelected(prefix);
+    PubSub.publish('PREFIX_CHANGED', prefix === '' ? null : Number(prefix));
+  };
+
+  return (
+    <FormControl size="small" sx={{ minWidth: 100 }}>
+      <InputLabel id="prefix-select-label">Prefix</InputLabel>
+      <Select
+        labelId="prefix-select-label"
+        value={selected}
+        label="Prefix"
+        onChange={handleChange}
+      >
+        <MenuItem value=""><em>None</em></MenuItem>
+        {Array.from({ length: 64 }, (_, i) => i + 1).map((prefix) => (
+          <MenuItem key={prefix} value={prefix.toString()}>/{prefix}</MenuItem>
+        ))}
+      </Select>
+    </FormControl>
+  );
+};
+
+export default PrefixSelect;
@@ -2,7 +2,8 @@ import React, { useEffect, useState } from 'react';
 import { Box, Typography, Alert } from '@mui/material';
 import { styled } from '@mui/material/styles';
 import PubSub from 'pubsub-js';
-import { CalculateResultMessage } from '../types';
+import { invoke } from "@tauri-apps/api/core";
+import { CalculateResultMessage, ModuleLocation } from '../types';
 
 const ResultBox = styled(Box)(({ theme }) => ({
   marginTop: theme.spacing(2),
@@ -30,6 +31,13 @@ const ErrorMessage = styled(Typography)(() => ({
   maxWidth: '100%'
 }));
 
+const TextResult = styled(Typography)(() => ({
+  whiteSpace: 'pre-wrap',
+  fontFamily: 'monospace',
+  overflowWrap: 'break-word',
+  wordBreak: 'break-word'
+}));
+
 interface ParsedResult {
   decimal: bigint | null;
   // original unsignedDecimal computed using minimal bitWidth two's complement
@@ -64,10 +72,13 @@ const ResultDisplay: React.FC = () => {
   const [error, setError] = useState<string | null>(null);
   const [parsedResult, setParsedResult] = useState<ParsedResult | null>(null);
   const [selectedBitWidth, setSelectedBitWidth] = useState<number>(64);
+  const [location, setLocation] = useState<ModuleLocation | null>(null);
+  const [text, setText] = useState<string | null>(null);
 
   useEffect(() => {
     const token = PubSub.subscribe('CALCULATE_RESULT', (_msg: string, data: CalculateResultMessage) => {
       const { bigIntResult, error, bitWidth } = data;
+      setText(data.text ?? null);
       if (!error && bigIntResult !== null) {
         try {
           const display = recalcRepresentation(bigIntResult, bitWidth);
@@ -80,6 +91,7 @@ const ResultDisplay: React.FC = () => {
           });
           setSelectedBitWidth(bitWidth);
           setError(null);
+          invoke<ModuleLocation | null>('where_is').then(setLocation).catch(() => setLocation(null));
         } catch (err) {
           setError((err as Error).message);
           setParsedResult(null);
@@ -87,6 +99,7 @@ const ResultDisplay: React.FC = () => {
       } else {
         setError(error);
         setParsedResult(null);
+        setLocation(null);
       }
     });
 
@@ -108,6 +121,11 @@ const ResultDisplay: React.FC = () => {
           </ErrorMessage>
         </Alert>
       )}
+      {text !== null && (
+        <TextResult className="selectable-text" variant="body1">
+          {text}
+        </TextResult>
+      )}
       {parsedResult && (
         <>
           <Typography className="selectable-text" variant="body1" color="primary">
@@ -125,6 +143,11 @@ const ResultDisplay: React.FC = () => {
           <Typography className="selectable-text" variant="body1" color="error">
             Hexadecimal: {displayRepresentation.hexadecimal}
           </Typography>
+          {location && (
+            <Typography className="selectable-text" variant="body1" color="textSecondary">
+              Module: {location.module}+{location.offset}{location.image ? ' (image)' : ''}
+            </Typography>
+          )}
         </>
       )}
     </ResultBox>
@@ -0,0 +1,58 @@
+import React, { useEffect, useState } from 'react';
+import { Box, Typography } from '@mui/material';
+import { styled } from '@mui/material/styles';
+import PubSub from 'pubsub-js';
+import { invoke } from "@tauri-apps/api/core";
+import { CalculateResultMessage, StatusMeaning } from '../types';
+
+const StatusBox = styled(Box)(({ theme }) => ({
+  padding: theme.spacing(1, 2),
+  border: `1px solid ${theme.palette.divider}`,
+  borderRadius: 10,
+  maxWidth: '600px',
+  minWidth: '440px',
+  margin: '0 auto',
+  textAlign: 'left',
+}));
+
+// Shows what the current value means as an errno, NTSTATUS, HRESULT or Win32 error
+const StatusPanel: React.FC = () => {
+  const [meanings, setMeanings] = useState<StatusMeaning[]>([]);
+
+  useEffect(() => {
+    const token = PubSub.subscribe('CALCULATE_RESULT', (_msg: string, data: CalculateResultMessage) => {
+      if (data.error || data.bigIntResult === null) {
+        setMeanings([]);
+        return;
+      }
+      invoke<StatusMeaning[]>('what_is').then(setMeanings).catch(() => setMeanings([]));
+    });
+
+    return () => {
+      PubSub.unsubscribe(token);
+    };
+  }, []);
+
+  if (meanings.length === 0) {
+    return null;
+  }
+
+  return (
+    <StatusBox>
+      {meanings.map((meaning) => (
+        <Box key={`${meaning.kind} ${meaning.name}`} mb={0.5}>
+          <Typography className="selectable-text" variant="body2">
+            <strong>{meaning.kind}</strong> {meaning.name}{meaning.description ? `: ${meaning.description}` : ''}
+          </Typography>
+          {meaning.detail && (
+            <Typography className="selectable-text" variant="caption" color="textSecondary">
+              {meaning.detail}
+            </Typography>
+          )}
+        </Box>
+      ))}
+    </StatusBox>
+  );
+};
+
+export default StatusPanel;
@@ -1,3 +1,4 @@
+import { CalculateResultMessage, EvalResult } from './types';
 
 // Helper function to compute minimal bitWidth for given signed number
 export const getMinimalBitWidth = (decimal: bigint): number => {
@@ -10,4 +11,26 @@ export const getMinimalBitWidth = (decimal: bigint): number => {
   } else {
     return 64;
   }
-};
\ No newline at end of file
+};
+
+// Parse a result string from the backend (e.g. "-0x1f") into a BigInt
+export const parseResult = (res: string): bigint => {
+  const isNegative = res.startsWith('-');
+  const value = BigInt(isNegative ? res.substring(1) : res);
+  return isNegative ? -value : value;
+};
+
+// Read the bits of an unsigned value as a signed number of the given width
+export const toSigned = (value: bigint, bitWidth: number): bigint => {
+  const width = BigInt(bitWidth);
+  return value >= (1n << (width - 1n)) ? value - (1n << width) : value;
+};
+
+// Turn an evaluation result into the message the panels listen to
+export const resultMessage = (res: EvalResult): CalculateResultMessage => {
+  if (res.value === null) {
+    return { bigIntResult: null, error: null, bitWidth: 8, text: res.text };
+  }
+  const value = parseResult(res.value);
+  return { bigIntResult: value, error: null, bitWidth: getMinimalBitWidth(value) };
+};
@@ -2,4 +2,45 @@ export interface CalculateResultMessage {
   bigIntResult: bigint | null;
   bitWidth: number;
   error: string | null;
+  // bytes and text results, which have no number
+  text?: string | null;
+}
+
+// A number comes as a hex string, bytes and text as they are shown
+export interface EvalResult {
+  value: string | null;
+  text: string | null;
+}
+
+export interface ValueResult {
+  value: string;
+  width: number;
+}
+
+export interface LayoutField {
+  name: string;
+  bits: { lo: number; hi: number };
+  description: string;
+}
+
+export interface HistoryItem {
+  id: number;
+  input: string;
+  result: string;
+  radix: string;
+  timestamp: number;
+  pinned: boolean;
+}
+
+export interface ModuleLocation {
+  module: string;
+  offset: string;
+  image: boolean;
+}
+
+export interface StatusMeaning {
+  kind: string;
+  name: string;
+  description: string;
+  detail: string | null;
 }